@@ -4,7 +4,7 @@ use crate::{
     player::PlayerHandle,
     tui,
     ui_state::{Mode, PopupType, SettingsMode, UiState},
-    Library,
+    Config, Library,
 };
 use ratatui::crossterm::{
     event::{
@@ -16,18 +16,23 @@ use ratatui::crossterm::{
 use std::sync::Arc;
 
 impl Concertus {
-    pub fn new() -> Self {
+    pub fn new(config: Config) -> Self {
         let lib = Arc::new(Library::init());
         let lib_clone = Arc::clone(&lib);
 
-        let player = PlayerHandle::spawn();
+        let player = PlayerHandle::spawn(&config);
         let metrics = player.metrics();
 
+        let mut ui = UiState::new(lib_clone, metrics);
+        ui.resume_after = config.resume_after();
+
         Concertus {
             library: lib,
             player,
-            ui: UiState::new(lib_clone, metrics),
+            ui,
             library_refresh_rec: None,
+            waveform_batch: None,
+            cued_song: None,
         }
     }
 
@@ -57,10 +62,16 @@ impl Concertus {
         // ================
         loop {
             self.select_shortcut(&key_rx);
+            if let Err(e) = self.check_sleep_timer() {
+                self.ui.set_error(e);
+            }
 
             terminal.draw(|f| tui::render(f, &mut self.ui))?;
+            self.player
+                .set_display_visible(self.ui.display_state.progress_visible)?;
 
             if self.ui.get_mode() == Mode::QUIT {
+                self.remember_position();
                 self.player.stop()?;
                 break;
             }
@@ -87,6 +98,11 @@ impl Concertus {
     pub fn initialize_ui(&mut self) {
         self.ui.soft_reset();
         let _ = self.ui.playback.load_history(self.library.get_songs_map());
+        let _ = self.ui.load_resume_positions();
         let _ = self.ui.restore_state();
+        let _ = self.restore_volume();
+        let _ = self.apply_equalizer();
+        let _ = self.restore_output_device();
+        let _ = self.restore_session();
     }
 }
@@ -1,7 +1,7 @@
 use crate::{app_core::Concertus, key_handler::Action, ui_state::Mode};
 use anyhow::Result;
 use crossbeam_channel::Receiver;
-use ratatui::crossterm::{self, event::KeyEvent};
+use ratatui::crossterm::{self, event::Event};
 
 impl Concertus {
     #[rustfmt::skip]
@@ -9,10 +9,34 @@ impl Concertus {
         match action {
             // Player 
             Action::Play            => self.play_selected_song()?,
+            Action::PlayFromStart   => self.play_from_start()?,
             Action::TogglePlayback  => self.player.toggle_playback()?,
-            Action::Stop            => self.player.stop()?,
+            Action::Stop            => self.stop()?,
             Action::SeekForward(s)  => self.player.seek_forward(s)?,
             Action::SeekBack(s)     => self.player.seek_back(s)?,
+            Action::SeekToPercent(p) => self.seek_to_percent(p)?,
+            Action::SeekPopup       => self.seek_popup(),
+            Action::SeekConfirm     => self.seek_confirm()?,
+            Action::AdjustVolume(v) => self.player.adjust_volume(v)?,
+            Action::ToggleMute      => self.player.toggle_mute()?,
+            Action::AdjustSpeed(d)  => self.adjust_speed(d)?,
+            Action::ResetSpeed      => self.player.set_speed(1.0)?,
+            Action::CycleRepeat     => self.cycle_repeat()?,
+            Action::SetLoopStart    => self.set_loop_start()?,
+            Action::SetLoopEnd      => self.set_loop_end()?,
+            Action::ClearLoop       => self.clear_loop()?,
+            Action::NextChapter     => self.next_chapter()?,
+            Action::PrevChapter     => self.prev_chapter()?,
+            Action::ChapterList     => self.ui.open_chapters(),
+            Action::ChapterConfirm  => self.chapter_confirm()?,
+            Action::OpenEqualizer   => self.ui.open_equalizer(),
+            Action::CycleEqPreset(dir) => self.cycle_eq_preset(dir)?,
+            Action::AdjustEqBand(d) => self.adjust_eq_band(d)?,
+            Action::RevertEqualizer => self.revert_equalizer()?,
+            Action::SleepTimer      => self.ui.open_sleep_timer(),
+            Action::SleepTimerConfirm => self.sleep_timer_confirm()?,
+            Action::CancelSleepTimer => self.set_sleep_timer(None)?,
+            Action::ToggleSleepFade => self.ui.toggle_sleep_fade(),
             Action::PlayNext        => self.play_next()?,
             Action::PlayPrev        => self.play_prev()?,
 
@@ -57,6 +81,9 @@ impl Concertus {
 
             Action::ShiftPosition(direction) => self.shift_position(direction)?,
             Action::IncrementWFSmoothness(direction) => self.ui.increment_wf_smoothness(direction),
+            Action::ZoomWaveform(direction) => self.ui.zoom_waveform(direction),
+            Action::PanWaveform(direction)  => self.ui.pan_waveform(direction),
+            Action::CenterWaveform          => self.ui.center_waveform(),
             Action::IncrementSidebarSize(x) => self.ui.adjust_sidebar_size(x),
 
             Action::SetProgressDisplay(p)   => self.ui.set_progress_display(p),
@@ -72,6 +99,8 @@ impl Concertus {
             Action::ClosePopup      => self.ui.close_popup(),
             Action::SoftReset       => self.ui.soft_reset(),
             Action::UpdateLibrary   => self.update_library()?,
+            Action::ToggleWaveformBatch => self.toggle_waveform_batch(),
+            Action::CancelWaveformBatch => self.cancel_waveform_batch(),
             Action::QUIT            => self.ui.set_mode(Mode::QUIT),
 
             Action::ViewSettings    => self.activate_settings(),
@@ -80,6 +109,8 @@ impl Concertus {
             Action::RootAdd         => self.settings_add_root(),
             Action::RootRemove      => self.settings_remove_root(),
             Action::RootConfirm     => self.settings_root_confirm()?,
+            Action::OutputDevices   => self.ui.view_output_devices(),
+            Action::OutputDeviceConfirm => self.settings_output_confirm()?,
 
             _ => (),
         }
@@ -87,15 +118,21 @@ impl Concertus {
     }
 }
 
-pub fn key_loop() -> Receiver<KeyEvent> {
+/// Forwards key presses, along with resizes as nothing else would trigger a
+/// redraw for them
+pub fn key_loop() -> Receiver<Event> {
     let (key_tx, key_rx) = crossbeam_channel::bounded(16);
 
     // 2. SPAWN the input thread (offloading)
     std::thread::spawn(move || loop {
-        if let Ok(crossterm::event::Event::Key(key)) = crossterm::event::read() {
-            if key.kind == crossterm::event::KeyEventKind::Press {
-                let _ = key_tx.try_send(key);
+        match crossterm::event::read() {
+            Ok(Event::Key(key)) if key.kind == crossterm::event::KeyEventKind::Press => {
+                let _ = key_tx.try_send(Event::Key(key));
             }
+            Ok(event @ Event::Resize(..)) => {
+                let _ = key_tx.try_send(event);
+            }
+            _ => (),
         }
     });
 
@@ -1,4 +1,4 @@
-use crate::{player::PlayerHandle, ui_state::UiState, Library};
+use crate::{app_core::waveforms::WaveformBatch, player::PlayerHandle, ui_state::UiState, Library};
 use crossbeam_channel::Receiver;
 use std::sync::Arc;
 
@@ -8,6 +8,8 @@ mod library;
 mod playback;
 mod player;
 mod select;
+mod sleep_timer;
+mod waveforms;
 
 pub use key_events::key_loop;
 
@@ -16,6 +18,9 @@ pub struct Concertus {
     pub(crate) ui: UiState,
     player: PlayerHandle,
     library_refresh_rec: Option<Receiver<LibraryRefreshProgress>>,
+    waveform_batch: Option<WaveformBatch>,
+    // Loaded paused and not yet heard, its play counts once it resumes
+    cued_song: Option<u64>,
 }
 
 pub enum LibraryRefreshProgress {
@@ -36,3 +41,9 @@ pub enum LibraryRefreshProgress {
     Complete(crate::Library),
     Error(String),
 }
+
+pub enum WaveformBatchProgress {
+    Processing { current: usize, total: usize },
+    Complete,
+    Error(String),
+}
@@ -12,7 +12,7 @@ use std::sync::Arc;
 
 impl Concertus {
     pub fn advance_to_next_gapless(&mut self) -> Option<Arc<ValidatedSong>> {
-        let (delta, next) = self.ui.playback.advance();
+        let (delta, next) = self.ui.playback.finish_track();
 
         if self.ui.get_mode() == Mode::Queue {
             self.ui.set_legal_songs();
@@ -29,6 +29,7 @@ impl Concertus {
     }
 
     pub fn queue_selection(&mut self, sel_type: SelectionType, shuffle: bool) -> Result<()> {
+        let is_context = sel_type != SelectionType::Multi;
         let mut songs = self.ui.get_songs_by_selection(sel_type)?;
         if songs.is_empty() {
             return Ok(());
@@ -39,6 +40,10 @@ impl Concertus {
         }
 
         if self.player.is_stopped() {
+            if is_context {
+                self.ui.playback.set_context(songs.clone());
+            }
+
             let first = songs.remove(0);
             let validated = ValidatedSong::new(&first)?;
             self.play_song(&validated)?;
@@ -158,21 +163,33 @@ impl Concertus {
         last_delta
     }
 
+    /// A next track with a resume position is left out, so that it starts
+    /// through `play_song` at that position once the current one ends
     pub fn force_sync(&self) -> Result<()> {
         let next = self
             .ui
             .playback
-            .peek_queue_validated()
+            .up_next_validated()
+            .filter(|_| !self.ui.playback.stops_after_current())
+            .filter(|s| self.ui.get_resume_position(&s.meta).is_none())
             .map(|s| ConcertusTrack::from(s.as_ref()));
         let _ = self.player.set_next(next);
         Ok(())
     }
 
-    /// Ensure that player's up_next value is always synced
+    /// Ensure that player's up_next value is always synced, and let event
+    /// subscribers know the queue moved
     pub fn sync_player(&self, delta: &QueueDelta) {
-        if let QueueDelta::HeadChanged { curr, .. } = delta {
-            let next = curr.as_ref().map(|s| ConcertusTrack::new(s.id(), s.path()));
-            let _ = self.player.set_next(next);
+        self.player.queue_changed(self.ui.playback.queue_ids());
+
+        if let QueueDelta::HeadChanged { .. } = delta {
+            let _ = self.force_sync();
         }
     }
+
+    pub fn cycle_repeat(&mut self) -> Result<()> {
+        let mode = self.ui.playback.repeat().cycle();
+        self.ui.playback.set_repeat(mode);
+        self.force_sync()
+    }
 }
@@ -3,27 +3,34 @@ use std::sync::Arc;
 
 use crate::{
     app_core::Concertus,
-    key_handler::SelectionType,
-    library::{SimpleSong, SongDatabase},
+    key_handler::{Incrementor, SelectionType},
+    library::{SimpleSong, SongDatabase, SongInfo},
+    parse_timestamp,
     playback::ValidatedSong,
     player::{ConcertusTrack, PlayerEvent},
-    ui_state::{LibraryView, Mode},
+    ui_state::{LibraryView, Mode, PopupType},
 };
 
 impl Concertus {
+    /// Long tracks pick up where they were last left off
     pub(crate) fn play_song(&mut self, song: &ValidatedSong) -> Result<()> {
-        let song = ConcertusTrack::from(song);
-        self.player.play(song)
+        self.ui.playback.drop_expired_sleep_timer();
+        let start = self.ui.get_resume_position(&song.meta).unwrap_or_default();
+        self.player.play(ConcertusTrack::from(song), start)
     }
 
     pub(crate) fn play_selected_song(&mut self) -> Result<()> {
         let song = self.ui.get_selected_song()?;
+        self.remember_position();
 
         if self.ui.get_mode() == &Mode::Queue {
             self.remove_song()?;
         }
 
         let validated = ValidatedSong::new(&song)?;
+        if let Some(context) = self.ui.get_repeat_context() {
+            self.ui.playback.set_context(context);
+        }
         self.play_song(&validated)?;
         self.force_sync()?;
 
@@ -31,6 +38,7 @@ impl Concertus {
     }
 
     pub(crate) fn play_next(&mut self) -> Result<()> {
+        self.remember_position();
         let (delta, next) = self.ui.playback.advance();
 
         match next {
@@ -46,6 +54,7 @@ impl Concertus {
     }
 
     pub(crate) fn play_prev(&mut self) -> Result<()> {
+        self.remember_position();
         let (delta, popped) = self
             .ui
             .playback
@@ -58,6 +67,37 @@ impl Concertus {
         Ok(())
     }
 
+    /// Plays the selected song from the top, forgetting where it was left
+    pub(crate) fn play_from_start(&mut self) -> Result<()> {
+        let song = self.ui.get_selected_song()?;
+        self.ui.clear_resume_position(song.id);
+        self.play_selected_song()
+    }
+
+    pub(crate) fn stop(&mut self) -> Result<()> {
+        self.remember_position();
+        self.player.stop()
+    }
+
+    /// Notes how far into the current track playback got, before leaving it.
+    /// Only tracks past the configured length are kept.
+    pub(crate) fn remember_position(&mut self) {
+        if self.player.is_stopped() {
+            return;
+        }
+
+        if let Some(song) = self.ui.get_now_playing().cloned() {
+            self.ui.save_resume_position(&song, self.player.elapsed());
+        }
+    }
+
+    /// A track which played to the end has nothing left to resume
+    fn forget_finished(&mut self) {
+        if let Some(id) = self.ui.get_now_playing().map(|s| s.id) {
+            self.ui.clear_resume_position(id);
+        }
+    }
+
     pub fn remove_song(&mut self) -> Result<()> {
         match self.ui.get_mode() {
             Mode::Queue => match self.ui.multi_select_empty() {
@@ -96,27 +136,196 @@ impl Concertus {
         Ok(())
     }
 
+    pub(crate) fn seek_popup(&mut self) {
+        if self.ui.get_now_playing().is_some() {
+            self.ui.show_popup(PopupType::SeekTo);
+        }
+    }
+
+    pub(crate) fn seek_confirm(&mut self) -> Result<()> {
+        let input = self.ui.get_popup_string();
+
+        if let Some(percent) = input.strip_suffix('%') {
+            match percent.trim().parse::<u8>() {
+                Ok(p) if p <= 100 => self.seek_to_percent(p)?,
+                _ => self.ui.set_error(anyhow!("Invalid percentage: {input}")),
+            }
+            return Ok(());
+        }
+
+        match parse_timestamp(&input) {
+            Some(pos) => {
+                self.player.seek_to(pos)?;
+                self.ui.close_popup();
+            }
+            None => self.ui.set_error(anyhow!(
+                "Invalid timestamp: {input}\nExpected mm:ss or h:mm:ss"
+            )),
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn seek_to_percent(&mut self, percent: u8) -> Result<()> {
+        let Some(song) = self.ui.get_now_playing() else {
+            return Ok(());
+        };
+
+        let target = song.get_duration().mul_f32(percent.min(100) as f32 / 100.0);
+        self.player.seek_to(target)?;
+
+        if self.ui.popup.current == PopupType::SeekTo {
+            self.ui.close_popup();
+        }
+
+        Ok(())
+    }
+
+    /// `tenths` of a step, rounded so repeated steps never drift off 0.1x
+    pub(crate) fn adjust_speed(&mut self, tenths: i8) -> Result<()> {
+        let speed = self.ui.get_playback_speed() + tenths as f32 / 10.0;
+        self.player.set_speed((speed * 10.0).round() / 10.0)
+    }
+
+    pub(crate) fn set_loop_start(&mut self) -> Result<()> {
+        if self.ui.get_now_playing().is_none() {
+            return Ok(());
+        }
+
+        let ab_loop = self.ui.playback.set_loop_start(self.player.elapsed());
+        self.player.set_loop(ab_loop.region())
+    }
+
+    pub(crate) fn set_loop_end(&mut self) -> Result<()> {
+        if self.ui.get_now_playing().is_none() {
+            return Ok(());
+        }
+
+        let ab_loop = self.ui.playback.set_loop_end(self.player.elapsed())?;
+        self.player.set_loop(ab_loop.region())
+    }
+
+    pub(crate) fn clear_loop(&mut self) -> Result<()> {
+        self.ui.playback.clear_loop();
+        self.player.set_loop(None)
+    }
+
+    pub(crate) fn next_chapter(&mut self) -> Result<()> {
+        match self.ui.next_chapter_start() {
+            Some(start) => self.player.seek_to(start),
+            None => Ok(()),
+        }
+    }
+
+    /// Restarts the current chapter, or steps back one when near its start
+    pub(crate) fn prev_chapter(&mut self) -> Result<()> {
+        match self.ui.prev_chapter_start() {
+            Some(start) => self.player.seek_to(start),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn chapter_confirm(&mut self) -> Result<()> {
+        if let Some(start) = self.ui.get_chapter_choice() {
+            self.player.seek_to(start)?;
+        }
+        self.ui.close_popup();
+        Ok(())
+    }
+
+    pub(crate) fn cycle_eq_preset(&mut self, dir: Incrementor) -> Result<()> {
+        self.ui.cycle_eq_preset(dir);
+        self.apply_equalizer()
+    }
+
+    pub(crate) fn adjust_eq_band(&mut self, db: i8) -> Result<()> {
+        self.ui.adjust_eq_band(db);
+        self.apply_equalizer()
+    }
+
+    pub(crate) fn revert_equalizer(&mut self) -> Result<()> {
+        self.ui.revert_equalizer();
+        self.apply_equalizer()
+    }
+
+    pub(super) fn apply_equalizer(&self) -> Result<()> {
+        self.player.set_equalizer(self.ui.get_eq_settings())
+    }
+
+    pub(super) fn restore_volume(&mut self) -> Result<()> {
+        if let Some((volume, muted)) = self.ui.saved_volume()? {
+            self.player.set_volume(volume)?;
+            if muted {
+                self.player.toggle_mute()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Picks up where the last session left off, paused so that playback
+    /// only continues once asked to
+    pub(super) fn restore_session(&mut self) -> Result<()> {
+        let resume = self
+            .ui
+            .playback
+            .load_session(self.library.get_songs_map())?;
+
+        if let Some((song, elapsed)) = resume {
+            self.player
+                .cue(ConcertusTrack::from(song.as_ref()), elapsed)?;
+        }
+
+        self.force_sync()
+    }
+
+    pub(crate) fn settings_output_confirm(&mut self) -> Result<()> {
+        if let Some(device) = self.ui.selected_output_device()
+            && device != self.ui.output_device
+        {
+            self.player.set_output_device(device.clone())?;
+            self.ui.output_device = device;
+        }
+
+        self.ui.close_popup();
+        Ok(())
+    }
+
+    /// A saved device which has since gone missing falls back to the default
+    pub(super) fn restore_output_device(&mut self) -> Result<()> {
+        if self.ui.output_device.is_some() {
+            self.player
+                .set_output_device(self.ui.output_device.clone())?;
+        }
+        Ok(())
+    }
+
     pub(super) fn handle_player_events(&mut self, event: PlayerEvent) -> Result<()> {
         match event {
             PlayerEvent::TrackStarted((this_song, was_gapless)) => {
                 let return_id = this_song.id();
 
+                // Tracks with a resume position are never queued gaplessly
                 if was_gapless {
+                    self.forget_finished();
                     self.advance_to_next_gapless();
                 }
-                let song = self.library.get_song_by_id(return_id).cloned();
-                self.ui.set_now_playing(song);
-
-                if let Some(song) = self.library.get_song_by_id(return_id).cloned() {
-                    song.update_play_count()?;
-                    self.ui.clear_waveform();
-                    self.ui.request_waveform(&song);
-                }
-
-                Ok(())
+                self.cued_song = None;
+                self.now_playing(return_id)?;
+                self.count_play(return_id)
+            }
+            PlayerEvent::TrackCued(this_song) => {
+                self.cued_song = Some(this_song.id());
+                self.now_playing(this_song.id())
             }
+            PlayerEvent::Resumed => match self.cued_song.take() {
+                Some(id) => self.count_play(id),
+                None => Ok(()),
+            },
             PlayerEvent::PlaybackStopped => {
-                let (delta, next) = self.ui.playback.advance();
+                self.cued_song = None;
+                self.forget_finished();
+                let (delta, next) = self.ui.playback.end_track();
+                self.ui.playback.clear_loop();
 
                 if let Some(song) = next {
                     self.play_song(&song)?;
@@ -125,15 +334,54 @@ impl Concertus {
                 }
 
                 self.ui.playback.set_now_playing(None);
+                self.ui.clear_chapters();
                 self.player.stop()?;
+                self.player.set_fade(1.0)?;
                 self.ui.clear_waveform();
                 self.ui.set_legal_songs();
                 Ok(())
             }
+            // Recovery is left to the player, which reports if it fails
+            PlayerEvent::OutputLost(_) => Ok(()),
+            // The UI reads these straight from the metrics, they are meant
+            // for other subscribers
+            PlayerEvent::Paused
+            | PlayerEvent::Seeked(_)
+            | PlayerEvent::VolumeChanged { .. }
+            | PlayerEvent::TrackEndingSoon => Ok(()),
+            // Never sent back to the app, which announced it to begin with
+            PlayerEvent::QueueChanged(_) => Ok(()),
             PlayerEvent::Error(e) => {
                 self.ui.set_error(anyhow!(e));
                 Ok(())
             }
         }
     }
+
+    fn now_playing(&mut self, id: u64) -> Result<()> {
+        let song = self.library.get_song_by_id(id).cloned();
+        self.ui.set_now_playing(song);
+        self.ui.load_chapters(id);
+
+        // The player drops its loop on every new track as well
+        self.ui.playback.clear_loop();
+
+        // Sleep timers and repeat modes decide what follows from what plays now
+        if self.ui.playback.next_follows_current() {
+            self.force_sync()?;
+        }
+
+        if let Some(song) = self.library.get_song_by_id(id) {
+            self.ui.clear_waveform();
+            self.ui.request_waveform(song);
+        }
+        Ok(())
+    }
+
+    fn count_play(&self, id: u64) -> Result<()> {
+        if let Some(song) = self.library.get_song_by_id(id) {
+            song.update_play_count()?;
+        }
+        Ok(())
+    }
 }
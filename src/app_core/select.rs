@@ -1,11 +1,16 @@
-use crossbeam_channel::{select, Receiver};
-use ratatui::crossterm::event::KeyEvent;
+use crossbeam_channel::{after, select, Receiver};
+use ratatui::crossterm::event::Event;
 
-use crate::{app_core::Concertus, key_handler, REFRESH_RATE};
+use crate::{app_core::Concertus, key_handler};
 
 impl Concertus {
+    /// Blocks until there's something new to draw. Besides input, player
+    /// events and background work, the frame tick only runs while something
+    /// on screen moves by itself.
     #[inline]
-    pub fn select_shortcut(&mut self, key_rx: &Receiver<KeyEvent>) {
+    pub fn select_shortcut(&mut self, key_rx: &Receiver<Event>) {
+        let frame = self.ui.next_frame().map_or_else(never, after);
+
         select! {
             recv(self.player.events()) -> event => {
             if let Ok(event) = event {
@@ -21,6 +26,12 @@ impl Concertus {
                 }
             }
 
+            recv(self.waveform_batch.as_ref().map_or(&never(), |b| b.receiver())) -> progress => {
+                if let Ok(progress) = progress {
+                    self.handle_waveform_batch_progress(progress)
+                }
+            }
+
             recv(&self.ui.wf_reciever().unwrap_or(&never())) -> result => {
             if let Ok(res) = result {
                 let now_playing = &self.ui.playback.get_now_playing().cloned();
@@ -28,15 +39,16 @@ impl Concertus {
                 }
             }
 
-            recv(key_rx) -> key => {
-                if let Ok(key) = key {
+            // Resizes only need the redraw that follows
+            recv(key_rx) -> event => {
+                if let Ok(Event::Key(key)) = event {
                     if let Some(action) = key_handler::handle_key_event(key, &self.ui) {
                         let _ = self.handle_action(action);
                         }
                     }
                 }
 
-            default(REFRESH_RATE) => {}
+            recv(frame) -> _ => {}
         }
     }
 }
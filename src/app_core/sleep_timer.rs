@@ -0,0 +1,70 @@
+use anyhow::Result;
+
+use crate::{
+    app_core::Concertus,
+    playback::{SleepTarget, SleepTimer},
+};
+
+impl Concertus {
+    pub(crate) fn set_sleep_timer(&mut self, target: Option<SleepTarget>) -> Result<()> {
+        match target {
+            Some(target) => {
+                let timer = SleepTimer::new(target, self.ui.sleep_fade);
+                let playing = self.ui.player_is_active();
+                self.ui.playback.arm_sleep_timer(timer, playing)?;
+            }
+            None => self.ui.playback.set_sleep_timer(None),
+        }
+        self.player.set_fade(1.0)?;
+
+        // Drops the gapless follow up when stopping after this track, or
+        // restores it when the timer is cancelled
+        self.force_sync()
+    }
+
+    pub(crate) fn sleep_timer_confirm(&mut self) -> Result<()> {
+        let choice = self.ui.get_sleep_choice();
+        self.ui.close_popup();
+        match choice {
+            Some(choice) => self.set_sleep_timer(Some(choice.target())),
+            None => Ok(()),
+        }
+    }
+
+    /// Fades out ahead of the timer, and ends playback once a timed sleep is
+    /// due. Track based timers end through `PlaybackSession::end_track`.
+    pub(crate) fn check_sleep_timer(&mut self) -> Result<()> {
+        let elapsed = self.player.elapsed();
+        let Some(remaining) = self.ui.playback.sleep_remaining(elapsed) else {
+            return Ok(());
+        };
+
+        if let Some(level) = self
+            .ui
+            .playback
+            .sleep_timer_mut()
+            .and_then(|t| t.update_fade(remaining))
+        {
+            self.player.set_fade(level)?;
+        }
+
+        let timed_out = self
+            .ui
+            .playback
+            .sleep_timer()
+            .is_some_and(|t| matches!(t.target, SleepTarget::At(_)));
+
+        if timed_out && remaining.is_zero() {
+            // Stopped by hand in the meantime, there is nothing left to end
+            if self.ui.player_is_active() {
+                self.stop()?;
+                let _ = self.ui.playback.end_track();
+                self.ui.clear_waveform();
+                self.ui.set_legal_songs();
+            }
+            self.set_sleep_timer(None)?;
+        }
+
+        Ok(())
+    }
+}
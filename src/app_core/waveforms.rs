@@ -0,0 +1,232 @@
+use crate::{
+    Database,
+    app_core::{Concertus, WaveformBatchProgress},
+    ui_state::{WaveformBatchStatus, generate_waveform},
+};
+use anyhow::anyhow;
+use crossbeam_channel::{Receiver, Sender};
+use std::{
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError},
+    thread,
+};
+
+/// Upper limit on songs decoded at once, whatever the core count. The batch
+/// is meant to trickle along behind playback, not compete with it.
+const MAX_WORKERS: usize = 4;
+
+/// Handle on a batch running in the background
+pub(crate) struct WaveformBatch {
+    control: Arc<BatchControl>,
+    receiver: Receiver<WaveformBatchProgress>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum BatchState {
+    Running,
+    Paused,
+    Cancelled,
+}
+
+struct BatchControl {
+    state: Mutex<BatchState>,
+    changed: Condvar,
+}
+
+impl BatchControl {
+    fn new() -> Self {
+        BatchControl {
+            state: Mutex::new(BatchState::Running),
+            changed: Condvar::new(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BatchState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn set(&self, state: BatchState) {
+        *self.lock() = state;
+        self.changed.notify_all();
+    }
+
+    /// Blocks for as long as the batch is paused. Returns false once it has
+    /// been cancelled.
+    fn proceed(&self) -> bool {
+        let mut state = self.lock();
+        while *state == BatchState::Paused {
+            state = self
+                .changed
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+
+        *state == BatchState::Running
+    }
+}
+
+impl WaveformBatch {
+    /// Generates waveforms for every song in `song_ids` that doesn't have
+    /// one stored yet, on at most `MAX_WORKERS` threads.
+    fn spawn(song_ids: Vec<u64>) -> Self {
+        let control = Arc::new(BatchControl::new());
+        let (tx, receiver) = crossbeam_channel::bounded(1);
+
+        let batch_control = Arc::clone(&control);
+        thread::spawn(move || {
+            let _ = match run_batch(song_ids, &batch_control, &tx) {
+                Ok(_) => tx.send(WaveformBatchProgress::Complete),
+                Err(e) => tx.send(WaveformBatchProgress::Error(e.to_string())),
+            };
+        });
+
+        WaveformBatch { control, receiver }
+    }
+
+    pub(crate) fn receiver(&self) -> &Receiver<WaveformBatchProgress> {
+        &self.receiver
+    }
+
+    /// Returns true when the batch is left paused
+    fn toggle_pause(&self) -> bool {
+        let mut state = self.control.lock();
+        *state = match *state {
+            BatchState::Running => BatchState::Paused,
+            BatchState::Paused => BatchState::Running,
+            BatchState::Cancelled => BatchState::Cancelled,
+        };
+        self.control.changed.notify_all();
+
+        *state == BatchState::Paused
+    }
+
+    fn is_paused(&self) -> bool {
+        *self.control.lock() == BatchState::Paused
+    }
+
+    fn cancel(&self) {
+        self.control.set(BatchState::Cancelled);
+    }
+}
+
+/// Collects the songs which still need a waveform, then hands them out to
+/// the workers. Results all come back here to be written through a single
+/// connection. Playback may be storing a waveform through the app's own
+/// worker at the same time, which the busy timeout set by `Database::open`
+/// waits out.
+fn run_batch(
+    song_ids: Vec<u64>,
+    control: &BatchControl,
+    tx: &Sender<WaveformBatchProgress>,
+) -> anyhow::Result<()> {
+    let mut db = Database::open()?;
+    let stored = db.get_waveform_ids()?;
+
+    let pending = song_ids
+        .into_iter()
+        .filter(|id| !stored.contains(id))
+        .filter_map(|id| Some((id, db.get_song_path(id).ok()?)))
+        .collect::<Vec<_>>();
+
+    let total = pending.len();
+    let _ = tx.send(WaveformBatchProgress::Processing { current: 0, total });
+
+    let (job_tx, job_rx) = crossbeam_channel::unbounded();
+    let (done_tx, done_rx) = crossbeam_channel::unbounded();
+    pending.into_iter().for_each(|job| {
+        let _ = job_tx.send(job);
+    });
+    drop(job_tx);
+
+    let workers = thread::available_parallelism()
+        .map_or(1, |n| n.get() / 2)
+        .clamp(1, MAX_WORKERS);
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let (job_rx, done_tx) = (job_rx.clone(), done_tx.clone());
+
+            scope.spawn(move || {
+                while control.proceed()
+                    && let Ok((id, path)) = job_rx.recv()
+                {
+                    let _ = done_tx.send((id, generate_waveform(path)));
+                }
+            });
+        }
+        drop(done_tx);
+
+        // Songs that fail to decode are counted, and left for playback to
+        // try again later. A failed write stops the batch, as the rest
+        // would only be decoded to be thrown away.
+        for (current, (id, waveform)) in done_rx.iter().enumerate() {
+            if let Ok(waveform) = waveform
+                && let Err(e) = db.set_waveform(id, &waveform)
+            {
+                control.set(BatchState::Cancelled);
+                return Err(anyhow!("Failed to store waveform: {e}"));
+            }
+
+            let _ = tx.send(WaveformBatchProgress::Processing {
+                current: current + 1,
+                total,
+            });
+        }
+
+        Ok(())
+    })
+}
+
+impl Concertus {
+    /// Starts precomputing waveforms for the library, or pauses and resumes
+    /// the batch which is already running
+    pub(crate) fn toggle_waveform_batch(&mut self) {
+        match &self.waveform_batch {
+            Some(batch) => {
+                let paused = batch.toggle_pause();
+                if let Some(status) = self.ui.get_waveform_batch() {
+                    self.ui
+                        .set_waveform_batch(Some(WaveformBatchStatus { paused, ..status }));
+                }
+            }
+            None => {
+                let song_ids = self.library.songs.keys().copied().collect();
+                self.waveform_batch = Some(WaveformBatch::spawn(song_ids));
+            }
+        }
+    }
+
+    /// Songs already being decoded are finished and stored, the rest are
+    /// left for the next batch
+    pub(crate) fn cancel_waveform_batch(&mut self) {
+        if let Some(batch) = self.waveform_batch.take() {
+            batch.cancel();
+        }
+        self.ui.set_waveform_batch(None);
+    }
+
+    pub(super) fn handle_waveform_batch_progress(&mut self, progress: WaveformBatchProgress) {
+        match progress {
+            WaveformBatchProgress::Processing { current, total } => {
+                let paused = self
+                    .waveform_batch
+                    .as_ref()
+                    .is_some_and(|batch| batch.is_paused());
+
+                self.ui.set_waveform_batch(Some(WaveformBatchStatus {
+                    current,
+                    total,
+                    paused,
+                }));
+            }
+            WaveformBatchProgress::Complete => {
+                self.ui.set_waveform_batch(None);
+                self.waveform_batch = None;
+            }
+            WaveformBatchProgress::Error(e) => {
+                self.ui.set_error(anyhow!(e));
+                self.ui.set_waveform_batch(None);
+                self.waveform_batch = None;
+            }
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use anyhow::{Result, anyhow, bail};
+use serde::Deserialize;
+use std::{fs, io::ErrorKind, path::PathBuf, time::Duration};
+
+use crate::{
+    CONFIG_DIRECTORY, CONFIG_FILENAME,
+    player::{BackendKind, ReplayGainConfig},
+};
+
+const DEFAULT_RESUME_AFTER: f32 = 20.0;
+
+/// Startup options read from `config.toml` in the concertus config
+/// directory. Command line flags take precedence over the file.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub backend: BackendKind,
+    /// Seconds to fade between tracks of different albums, 0 to disable
+    pub crossfade: f32,
+    pub replaygain: ReplayGainConfig,
+    /// Minutes a track must run before its position is remembered between
+    /// plays, 0 to disable
+    pub resume_after: Option<f32>,
+}
+
+impl Config {
+    pub fn load(args: impl Iterator<Item = String>) -> Result<Self> {
+        let mut config = Self::from_file()?;
+        config.apply_args(args)?;
+        Ok(config)
+    }
+
+    pub fn crossfade(&self) -> Duration {
+        Duration::try_from_secs_f32(self.crossfade).unwrap_or_default()
+    }
+
+    pub fn resume_after(&self) -> Option<Duration> {
+        let minutes = self.resume_after.unwrap_or(DEFAULT_RESUME_AFTER);
+        Duration::try_from_secs_f32(minutes * 60.0)
+            .ok()
+            .filter(|d| !d.is_zero())
+    }
+
+    fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(CONFIG_DIRECTORY).join(CONFIG_FILENAME))
+    }
+
+    fn from_file() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents)
+                .map_err(|e| anyhow!("Invalid config at {}\n{e}", path.display())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn apply_args(&mut self, mut args: impl Iterator<Item = String>) -> Result<()> {
+        while let Some(arg) = args.next() {
+            let (flag, value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+
+            match flag.as_str() {
+                "-b" | "--backend" => {
+                    let value = value
+                        .or_else(|| args.next())
+                        .ok_or_else(|| anyhow!("{flag} requires a value"))?;
+                    self.backend = value.parse()?;
+                }
+                _ => bail!("Unrecognized argument: {flag}"),
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,26 @@
+use anyhow::Result;
+use rusqlite::params;
+use std::time::Duration;
+
+use crate::{Database, database::queries::GET_CHAPTERS, library::Chapter};
+
+impl Database {
+    pub fn get_chapters(&mut self, id: u64) -> Result<Vec<Chapter>> {
+        let chapters = self
+            .conn
+            .prepare(GET_CHAPTERS)?
+            .query_map(params![id.to_le_bytes()], |row| {
+                let title: String = row.get("title")?;
+                let start: f64 = row.get("start")?;
+                Ok((title, start))
+            })?
+            .filter_map(Result::ok)
+            .filter_map(|(title, start)| {
+                let start = Duration::try_from_secs_f64(start).ok()?;
+                Some(Chapter { title, start })
+            })
+            .collect();
+
+        Ok(chapters)
+    }
+}
@@ -1,7 +1,9 @@
 use crate::{
     CONFIG_DIRECTORY, DATABASE_FILENAME, SongMap,
-    database::tables::CREATE_TABLES,
-    library::{LongSong, SimpleSong, SongInfo},
+    database::tables::{
+        ADD_REPLAYGAIN_COLUMNS, ADD_RESCAN_COLUMN, CREATE_TABLES, RECREATE_WAVEFORMS,
+    },
+    library::{FileType, LongSong, ReplayGain, SimpleSong, SongInfo, WaveformPeaks},
 };
 use anyhow::Result;
 use queries::*;
@@ -14,14 +16,21 @@ use std::{
     time::{Duration, UNIX_EPOCH},
 };
 
+mod chapters;
 mod playlists;
 mod queries;
+mod resume;
+mod session;
 mod snapshot;
 mod tables;
 mod worker;
 
 pub(crate) const DB_BOUND: usize = 100;
+/// How long a write waits on another connection holding the database, e.g.
+/// the waveform batch and the app's own worker both storing waveforms
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
 
+pub use session::SavedSession;
 pub use worker::DbWorker;
 
 pub struct Database {
@@ -39,6 +48,7 @@ impl Database {
         fs::create_dir_all(&db_path).expect("Failed to create or access config directory");
 
         let conn = Connection::open(db_path.join(DATABASE_FILENAME))?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
 
         conn.pragma_update(None, "journal_mode", "WAL")?;
         conn.pragma_update(None, "foreign_keys", "ON")?;
@@ -56,7 +66,29 @@ impl Database {
 
     fn create_tables(&mut self) -> Result<()> {
         let tx = self.conn.transaction()?;
+        let has_chapters: bool = tx.query_row(HAS_CHAPTERS_TABLE, [], |r| r.get(0))?;
         tx.execute_batch(&CREATE_TABLES)?;
+
+        let has_replaygain: bool = tx.query_row(HAS_REPLAYGAIN_COLUMNS, [], |r| r.get(0))?;
+        if !has_replaygain {
+            tx.execute_batch(ADD_REPLAYGAIN_COLUMNS)?;
+        }
+
+        let has_rescan: bool = tx.query_row(HAS_RESCAN_COLUMN, [], |r| r.get(0))?;
+        if !has_rescan {
+            tx.execute_batch(ADD_RESCAN_COLUMN)?;
+        }
+
+        // Chapters are only read from mp4 containers
+        if !has_chapters {
+            tx.execute(FLAG_FORMAT_FOR_RESCAN, params![FileType::M4A])?;
+        }
+
+        let has_levels: bool = tx.query_row(HAS_WAVEFORM_LEVELS, [], |r| r.get(0))?;
+        if !has_levels {
+            tx.execute_batch(RECREATE_WAVEFORMS)?;
+        }
+
         tx.commit()?;
 
         Ok(())
@@ -73,6 +105,8 @@ impl Database {
         let tx = self.conn.transaction()?;
         {
             let mut stmt = tx.prepare_cached(INSERT_SONG)?;
+            let mut clear_chapters = tx.prepare_cached(DELETE_CHAPTERS)?;
+            let mut insert_chapter = tx.prepare_cached(INSERT_CHAPTER)?;
 
             for song in song_list {
                 // Get artist ID for the song's artist
@@ -106,8 +140,22 @@ impl Database {
                     &song.channels,
                     &song.bit_rate,
                     &song.sample_rate,
-                    &song.filetype
+                    &song.filetype,
+                    &song.replay_gain.track_gain,
+                    &song.replay_gain.track_peak,
+                    &song.replay_gain.album_gain,
+                    &song.replay_gain.album_peak,
                 ])?;
+
+                clear_chapters.execute(params![song.id.to_le_bytes()])?;
+                for (idx, chapter) in song.chapters.iter().enumerate() {
+                    insert_chapter.execute(params![
+                        song.id.to_le_bytes(),
+                        idx as i64,
+                        &chapter.title,
+                        chapter.start.as_secs_f64(),
+                    ])?;
+                }
             }
         }
         tx.commit()?;
@@ -194,6 +242,73 @@ impl Database {
         Ok(output)
     }
 
+    pub(crate) fn get_replay_gain(&mut self, id: u64) -> Result<ReplayGain> {
+        let output = self
+            .conn
+            .query_row(GET_REPLAY_GAIN, [id.to_le_bytes()], |r| {
+                Ok(ReplayGain {
+                    track_gain: r.get(0)?,
+                    track_peak: r.get(1)?,
+                    album_gain: r.get(2)?,
+                    album_peak: r.get(3)?,
+                })
+            })?;
+        Ok(output)
+    }
+
+    /// Songs flagged by a migration, which need their tags read again
+    pub(crate) fn get_rescan_paths(&mut self) -> Result<Vec<(u64, PathBuf)>> {
+        let paths = self
+            .conn
+            .prepare(GET_RESCAN_PATHS)?
+            .query_map([], |row| {
+                let hash_bytes: Vec<u8> = row.get("id")?;
+                let hash_array: [u8; 8] = hash_bytes
+                    .try_into()
+                    .expect("Failed to convert hash bytes to array");
+                let path: String = row.get("path")?;
+                Ok((u64::from_le_bytes(hash_array), PathBuf::from(path)))
+            })?
+            .filter_map(Result::ok)
+            .collect();
+
+        Ok(paths)
+    }
+
+    /// Updates the rows in place, as replacing them would cascade into
+    /// everything else that refers to the song
+    pub(crate) fn update_rescanned(&mut self, songs: &[(u64, LongSong)]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(UPDATE_REPLAY_GAIN)?;
+            let mut clear_chapters = tx.prepare_cached(DELETE_CHAPTERS)?;
+            let mut insert_chapter = tx.prepare_cached(INSERT_CHAPTER)?;
+
+            for (id, song) in songs {
+                let rg = &song.replay_gain;
+                stmt.execute(params![
+                    id.to_le_bytes(),
+                    &rg.track_gain,
+                    &rg.track_peak,
+                    &rg.album_gain,
+                    &rg.album_peak,
+                ])?;
+
+                clear_chapters.execute(params![id.to_le_bytes()])?;
+                for (idx, chapter) in song.chapters.iter().enumerate() {
+                    insert_chapter.execute(params![
+                        id.to_le_bytes(),
+                        idx as i64,
+                        &chapter.title,
+                        chapter.start.as_secs_f64(),
+                    ])?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
     pub(crate) fn get_hashes(&mut self) -> Result<HashSet<u64>> {
         let map = self
             .conn
@@ -317,24 +432,34 @@ impl Database {
     //   WAVEFORMS
     // =============
 
-    pub fn get_waveform(&mut self, id: u64) -> Result<Vec<f32>> {
-        let blob: Vec<u8> =
+    pub fn get_waveform(&mut self, id: u64) -> Result<WaveformPeaks> {
+        let (levels, blob): (u8, Vec<u8>) =
             self.conn
-                .query_row(GET_WAVEFORM, params![id.to_le_bytes()], |row| row.get(0))?;
+                .query_row(GET_WAVEFORM, params![id.to_le_bytes()], |row| {
+                    Ok((row.get(0)?, row.get(1)?))
+                })?;
 
-        let waveform = blob
-            .chunks_exact(4)
-            .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+        WaveformPeaks::from_blob(levels as usize, &blob)
+    }
+
+    /// Songs which already have a waveform stored
+    pub fn get_waveform_ids(&mut self) -> Result<HashSet<u64>> {
+        let ids = self
+            .conn
+            .prepare(GET_WAVEFORM_IDS)?
+            .query_map([], |row| row.get::<_, Vec<u8>>("song_id"))?
+            .filter_map(Result::ok)
+            .filter_map(|id| Some(u64::from_le_bytes(id.try_into().ok()?)))
             .collect();
 
-        Ok(waveform)
+        Ok(ids)
     }
 
-    pub fn set_waveform(&mut self, id: u64, wf: &[f32]) -> Result<()> {
-        let bytes: Vec<u8> = wf.iter().flat_map(|&f| f.to_le_bytes()).collect();
-
-        self.conn
-            .execute(INSERT_WAVEFORM, params![id.to_le_bytes(), bytes])?;
+    pub fn set_waveform(&mut self, id: u64, wf: &WaveformPeaks) -> Result<()> {
+        self.conn.execute(
+            INSERT_WAVEFORM,
+            params![id.to_le_bytes(), wf.levels() as u8, wf.to_blob()],
+        )?;
 
         Ok(())
     }
@@ -1,11 +1,15 @@
 pub const GET_WAVEFORM: &str = "
-    SELECT waveform FROM waveforms
+    SELECT levels, waveform FROM waveforms
     WHERE song_id = ?
 ";
 
+pub const GET_WAVEFORM_IDS: &str = "
+    SELECT song_id FROM waveforms
+";
+
 pub const INSERT_WAVEFORM: &str = "
-    INSERT or IGNORE INTO waveforms (song_id, waveform)
-    VALUES (?1, ?2)
+    INSERT or IGNORE INTO waveforms (song_id, levels, waveform)
+    VALUES (?1, ?2, ?3)
 ";
 
 pub const GET_ALL_SONGS: &str = "
@@ -44,10 +48,59 @@ pub const INSERT_SONG: &str = "
         channels,
         bit_rate,
         sample_rate, 
-        format
-    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13
+        format,
+        track_gain,
+        track_peak,
+        album_gain,
+        album_peak
+    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17
 )";
 
+pub const GET_REPLAY_GAIN: &str = "
+    SELECT track_gain, track_peak, album_gain, album_peak FROM songs
+    WHERE id = ?
+";
+
+pub const HAS_WAVEFORM_LEVELS: &str = "
+    SELECT COUNT(*) FROM pragma_table_info('waveforms')
+    WHERE name = 'levels'
+";
+
+pub const HAS_REPLAYGAIN_COLUMNS: &str = "
+    SELECT COUNT(*) FROM pragma_table_info('songs')
+    WHERE name = 'track_gain'
+";
+
+pub const HAS_RESCAN_COLUMN: &str = "
+    SELECT COUNT(*) FROM pragma_table_info('songs')
+    WHERE name = 'rescan'
+";
+
+pub const HAS_CHAPTERS_TABLE: &str = "
+    SELECT COUNT(*) FROM sqlite_master
+    WHERE type = 'table' AND name = 'chapters'
+";
+
+pub const FLAG_FORMAT_FOR_RESCAN: &str = "
+    UPDATE songs SET rescan = 1
+    WHERE format = ?
+";
+
+pub const GET_RESCAN_PATHS: &str = "
+    SELECT id, path FROM songs
+    WHERE rescan = 1
+";
+
+pub const UPDATE_REPLAY_GAIN: &str = "
+    UPDATE songs SET
+        track_gain = ?2,
+        track_peak = ?3,
+        album_gain = ?4,
+        album_peak = ?5,
+        rescan = 0
+    WHERE id = ?1
+";
+
 pub const INSERT_ARTIST: &str = "
     INSERT OR IGNORE INTO artists (
     name
@@ -115,6 +168,45 @@ pub const DELETE_FROM_HISTORY: &str = "
         (SELECT id FROM history ORDER BY timestamp DESC LIMIT 50)
 ";
 
+pub const LOAD_QUEUE: &str = "
+    SELECT song_id FROM queue
+    ORDER BY position ASC
+";
+
+pub const INSERT_INTO_QUEUE: &str = "
+    INSERT INTO queue (position, song_id) VALUES (?, ?)
+";
+
+pub const CLEAR_QUEUE: &str = "
+    DELETE FROM queue
+";
+
+pub const GET_RESUME_POSITIONS: &str = "
+    SELECT song_id, position FROM resume_positions
+";
+
+pub const SET_RESUME_POSITION: &str = "
+    INSERT OR REPLACE INTO resume_positions (song_id, position) VALUES (?, ?)
+";
+
+pub const DELETE_RESUME_POSITION: &str = "
+    DELETE FROM resume_positions WHERE song_id = ?
+";
+
+pub const GET_CHAPTERS: &str = "
+    SELECT title, start FROM chapters
+    WHERE song_id = ?
+    ORDER BY start ASC
+";
+
+pub const INSERT_CHAPTER: &str = "
+    INSERT INTO chapters (song_id, idx, title, start) VALUES (?, ?, ?, ?)
+";
+
+pub const DELETE_CHAPTERS: &str = "
+    DELETE FROM chapters WHERE song_id = ?
+";
+
 pub const UPDATE_PLAY_COUNT: &str = "
     INSERT INTO plays 
         (song_id, count)
@@ -129,6 +221,10 @@ pub const GET_UI_SNAPSHOT: &str = "
         FROM session_state 
         WHERE key LIKE 'ui_%'";
 
+pub const GET_SESSION_VALUE: &str = "
+    SELECT value FROM session_state WHERE key = ?
+";
+
 pub const SET_SESSION_STATE: &str = "
     INSERT OR REPLACE INTO session_state (key, value)
         VALUES (?, ?)
@@ -0,0 +1,44 @@
+use anyhow::Result;
+use rusqlite::params;
+use std::{collections::HashMap, time::Duration};
+
+use crate::{
+    Database,
+    database::queries::{DELETE_RESUME_POSITION, GET_RESUME_POSITIONS, SET_RESUME_POSITION},
+};
+
+impl Database {
+    pub fn get_resume_positions(&mut self) -> Result<HashMap<u64, Duration>> {
+        let positions = self
+            .conn
+            .prepare(GET_RESUME_POSITIONS)?
+            .query_map([], |row| {
+                let id: Vec<u8> = row.get("song_id")?;
+                let secs: f64 = row.get("position")?;
+                Ok((id, secs))
+            })?
+            .filter_map(Result::ok)
+            .filter_map(|(id, secs)| {
+                let id = u64::from_le_bytes(id.try_into().ok()?);
+                Some((id, Duration::try_from_secs_f64(secs).ok()?))
+            })
+            .collect();
+
+        Ok(positions)
+    }
+
+    /// `None` forgets the position, e.g. once the song is finished
+    pub fn set_resume_position(&mut self, id: u64, position: Option<Duration>) -> Result<()> {
+        match position {
+            Some(pos) => self.conn.execute(
+                SET_RESUME_POSITION,
+                params![id.to_le_bytes(), pos.as_secs_f64()],
+            )?,
+            None => self
+                .conn
+                .execute(DELETE_RESUME_POSITION, params![id.to_le_bytes()])?,
+        };
+
+        Ok(())
+    }
+}
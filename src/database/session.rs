@@ -0,0 +1,84 @@
+use anyhow::Result;
+use rusqlite::params;
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    Database, SongMap,
+    database::queries::{
+        CLEAR_QUEUE, GET_SESSION_VALUE, INSERT_INTO_QUEUE, LOAD_QUEUE, SET_SESSION_STATE,
+    },
+    library::SimpleSong,
+};
+
+const NOW_PLAYING_KEY: &str = "playback_song";
+const ELAPSED_KEY: &str = "playback_elapsed";
+
+/// Playback as it stood when the app was last closed
+pub struct SavedSession {
+    pub queue: Vec<Arc<SimpleSong>>,
+    pub now_playing: Option<Arc<SimpleSong>>,
+    pub elapsed: Duration,
+}
+
+impl Database {
+    pub fn save_session(
+        &mut self,
+        queue: &[u64],
+        now_playing: Option<u64>,
+        elapsed: Duration,
+    ) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            tx.execute(CLEAR_QUEUE, [])?;
+
+            let mut stmt = tx.prepare(INSERT_INTO_QUEUE)?;
+            for (idx, song_id) in queue.iter().enumerate() {
+                stmt.execute(params![idx as i64, song_id.to_le_bytes()])?;
+            }
+
+            // Saved as empty rather than removed, the upsert can't delete
+            let now_playing = now_playing.map(|id| id.to_string()).unwrap_or_default();
+
+            let mut stmt = tx.prepare(SET_SESSION_STATE)?;
+            stmt.execute(params![NOW_PLAYING_KEY, now_playing])?;
+            stmt.execute(params![ELAPSED_KEY, elapsed.as_secs_f64().to_string()])?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Songs which have left the library since are dropped
+    pub fn import_session(&mut self, song_map: &SongMap) -> Result<SavedSession> {
+        let queue = self
+            .conn
+            .prepare(LOAD_QUEUE)?
+            .query_map([], |row| row.get::<_, Vec<u8>>("song_id"))?
+            .filter_map(Result::ok)
+            .filter_map(|bytes| Some(u64::from_le_bytes(bytes.try_into().ok()?)))
+            .filter_map(|id| song_map.get(&id).cloned())
+            .collect();
+
+        let now_playing = self
+            .get_session_value(NOW_PLAYING_KEY)
+            .and_then(|id| id.parse::<u64>().ok())
+            .and_then(|id| song_map.get(&id).cloned());
+
+        let elapsed = self
+            .get_session_value(ELAPSED_KEY)
+            .and_then(|secs| secs.parse::<f64>().ok())
+            .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+            .unwrap_or_default();
+
+        Ok(SavedSession {
+            queue,
+            now_playing,
+            elapsed,
+        })
+    }
+
+    fn get_session_value(&mut self, key: &str) -> Option<String> {
+        self.conn
+            .query_row(GET_SESSION_VALUE, params![key], |row| row.get(0))
+            .ok()
+    }
+}
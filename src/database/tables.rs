@@ -18,6 +18,11 @@ pub const CREATE_TABLES: &str = r"
         bit_rate INTEGER,
         sample_rate INTEGER,
         format INTEGER,
+        track_gain REAL,
+        track_peak REAL,
+        album_gain REAL,
+        album_peak REAL,
+        rescan INTEGER NOT NULL DEFAULT 0,
         FOREIGN KEY(artist_id) REFERENCES artists(id),
         FOREIGN KEY(album_id) REFERENCES albums(id)
     );
@@ -37,6 +42,7 @@ pub const CREATE_TABLES: &str = r"
 
     CREATE TABLE IF NOT EXISTS waveforms(
         song_id BLOB PRIMARY KEY,
+        levels INTEGER NOT NULL,
         waveform BLOB,
         FOREIGN KEY(song_id) REFERENCES songs(id) ON DELETE CASCADE
     );
@@ -54,6 +60,27 @@ pub const CREATE_TABLES: &str = r"
         FOREIGN KEY(song_id) REFERENCES songs(id) ON DELETE CASCADE
     );
 
+    CREATE TABLE IF NOT EXISTS queue(
+        position INTEGER PRIMARY KEY,
+        song_id BLOB NOT NULL,
+        FOREIGN KEY(song_id) REFERENCES songs(id) ON DELETE CASCADE
+    );
+
+    CREATE TABLE IF NOT EXISTS resume_positions(
+        song_id BLOB PRIMARY KEY,
+        position REAL NOT NULL,
+        FOREIGN KEY(song_id) REFERENCES songs(id) ON DELETE CASCADE
+    );
+
+    CREATE TABLE IF NOT EXISTS chapters(
+        song_id BLOB NOT NULL,
+        idx INTEGER NOT NULL,
+        title TEXT NOT NULL,
+        start REAL NOT NULL,
+        PRIMARY KEY(song_id, idx),
+        FOREIGN KEY(song_id) REFERENCES songs(id) ON DELETE CASCADE
+    );
+
     CREATE TABLE IF NOT EXISTS session_state(
         key TEXT PRIMARY KEY,
         value TEXT NOT NULL
@@ -75,3 +102,32 @@ pub const CREATE_TABLES: &str = r"
         UNIQUE(playlist_id, position)
     );
 ";
+
+// Databases created before ReplayGain support
+pub const ADD_REPLAYGAIN_COLUMNS: &str = "
+    ALTER TABLE songs ADD COLUMN track_gain REAL;
+    ALTER TABLE songs ADD COLUMN track_peak REAL;
+    ALTER TABLE songs ADD COLUMN album_gain REAL;
+    ALTER TABLE songs ADD COLUMN album_peak REAL;
+";
+
+// Rescans skip files whose hash is already stored, so songs scanned before a
+// column or table existed are flagged to have their tags read again. Songs
+// without any gain stored may well predate ReplayGain support.
+pub const ADD_RESCAN_COLUMN: &str = "
+    ALTER TABLE songs ADD COLUMN rescan INTEGER NOT NULL DEFAULT 0;
+    UPDATE songs SET rescan = 1
+    WHERE track_gain IS NULL AND album_gain IS NULL;
+";
+
+// Waveforms used to be a single level of RMS values, which can't be turned
+// into peaks. They're dropped, and generated again as songs are played.
+pub const RECREATE_WAVEFORMS: &str = "
+    DROP TABLE waveforms;
+    CREATE TABLE waveforms(
+        song_id BLOB PRIMARY KEY,
+        levels INTEGER NOT NULL,
+        waveform BLOB,
+        FOREIGN KEY(song_id) REFERENCES songs(id) ON DELETE CASCADE
+    );
+";
@@ -1,15 +1,16 @@
 use crate::{
     SongMap,
     database::{DB_BOUND, Database},
-    library::SimpleSong,
+    library::{Chapter, SimpleSong, WaveformPeaks},
     ui_state::UiSnapshot,
 };
 use anyhow::{Result, anyhow};
 use indexmap::IndexMap;
 use std::{
-    collections::{HashSet, VecDeque},
+    collections::{HashMap, HashSet, VecDeque},
     sync::Arc,
     thread,
+    time::Duration,
 };
 
 pub enum DbMessage {
@@ -132,6 +133,15 @@ impl DbWorker {
         self.execute_sync(move |db| db.save_history_to_db(&history))
     }
 
+    pub fn save_session(
+        &self,
+        queue: Vec<u64>,
+        now_playing: Option<u64>,
+        elapsed: Duration,
+    ) -> Result<()> {
+        self.execute_sync(move |db| db.save_session(&queue, now_playing, elapsed))
+    }
+
     pub fn save_ui_snapshot(&self, snapshot: UiSnapshot) -> Result<()> {
         self.execute_sync(move |db| db.save_ui_snapshot(snapshot))
     }
@@ -162,7 +172,21 @@ impl DbWorker {
         });
     }
 
-    pub fn set_waveform(&self, song_id: u64, waveform: Vec<f32>) {
+    pub fn get_resume_positions(&self) -> Result<HashMap<u64, Duration>> {
+        self.execute_sync(move |db| db.get_resume_positions())
+    }
+
+    pub fn set_resume_position(&self, song_id: u64, position: Option<Duration>) {
+        self.execute(move |db| {
+            let _ = db.set_resume_position(song_id, position);
+        });
+    }
+
+    pub fn get_chapters(&self, song_id: u64) -> Result<Vec<Chapter>> {
+        self.execute_sync(move |db| db.get_chapters(song_id))
+    }
+
+    pub fn set_waveform(&self, song_id: u64, waveform: WaveformPeaks) {
         self.execute(move |db| {
             let _ = db.set_waveform(song_id, &waveform);
         });
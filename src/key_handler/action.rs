@@ -18,7 +18,7 @@ pub fn handle_key_event(key_event: KeyEvent, state: &UiState) -> Option<Action>
 
     match state.get_input_context() {
         InputContext::Popup(popup)  => handle_popup(&key_event, &popup),
-        InputContext::Fullscreen    => handle_fullscreen(&key_event),
+        InputContext::Fullscreen    => handle_fullscreen(&key_event, state),
         InputContext::TrackList(_)  => handle_tracklist(&key_event, &state),
         InputContext::AlbumView     => handle_album_browser(&key_event),
         InputContext::PlaylistView  => handle_playlist_browswer(&key_event),
@@ -65,6 +65,28 @@ fn global_commands(key: &KeyEvent, state: &UiState) -> Option<Action> {
 
             (X, Char('p')) => Some(Action::SeekBack(SEEK_SMALL)),
             (S, Char('P')) => Some(Action::SeekBack(SEEK_LARGE)),
+            (X, Char('t')) => Some(Action::SeekPopup),
+
+            (X, Char('=')) | (S, Char('+')) => Some(Action::AdjustVolume(VOLUME_STEP)),
+            (X, Char('-')) => Some(Action::AdjustVolume(-VOLUME_STEP)),
+            (X, Char('m')) => Some(Action::ToggleMute),
+
+            (X, Char('.')) => Some(Action::AdjustSpeed(SPEED_STEP)),
+            (X, Char(',')) => Some(Action::AdjustSpeed(-SPEED_STEP)),
+            (X, Backspace) => Some(Action::ResetSpeed),
+            (S, Char('R')) => Some(Action::CycleRepeat),
+
+            (S, Char('(')) => Some(Action::SetLoopStart),
+            (S, Char(')')) => Some(Action::SetLoopEnd),
+            (X, Char('\\')) => Some(Action::ClearLoop),
+
+            (X, Char('\'')) => Some(Action::NextChapter),
+            (X, Char(';')) => Some(Action::PrevChapter),
+            (S, Char('"')) => Some(Action::ChapterList),
+
+            (X, Char('e')) => Some(Action::OpenEqualizer),
+            (X, Char('z')) => Some(Action::SleepTimer),
+            (S, Char('E')) => Some(Action::CycleEqPreset(Incrementor::Down)),
 
             // NAVIGATION
             (X, Char('/')) => Some(Action::ChangeMode(Mode::Search)),
@@ -97,10 +119,16 @@ fn global_commands(key: &KeyEvent, state: &UiState) -> Option<Action> {
             (X, Char('w')) => Some(Action::SetProgressDisplay(ProgressDisplay::Waveform)),
             (X, Char('o')) => Some(Action::SetProgressDisplay(ProgressDisplay::Oscilloscope)),
             (X, Char('b')) => Some(Action::SetProgressDisplay(ProgressDisplay::ProgressBar)),
+            (X, Char('y')) => Some(Action::SetProgressDisplay(ProgressDisplay::Spectrum)),
+            (X, Char('i')) => Some(Action::SetProgressDisplay(state.next_stereo_display())),
             (S, Char('W')) => Some(Action::SetFullscreen(ProgressDisplay::Waveform)),
             (S, Char('O')) => Some(Action::SetFullscreen(ProgressDisplay::Oscilloscope)),
             (S, Char('B')) => Some(Action::SetFullscreen(ProgressDisplay::ProgressBar)),
+            (S, Char('Y')) => Some(Action::SetFullscreen(ProgressDisplay::Spectrum)),
+            (S, Char('I')) => Some(Action::SetFullscreen(state.next_stereo_display())),
             (C, Char('u')) | (X, F(5)) => Some(Action::UpdateLibrary),
+            (X, F(7)) => Some(Action::ToggleWaveformBatch),
+            (X, F(8)) => Some(Action::CancelWaveformBatch),
 
             _ => None,
         },
@@ -111,6 +139,7 @@ fn global_commands(key: &KeyEvent, state: &UiState) -> Option<Action> {
 fn handle_tracklist(key: &KeyEvent, state: &UiState) -> Option<Action> {
     let base_action = match (key.modifiers, key.code) {
         (X, Enter) => Some(Action::Play),
+        (X, Char('r')) => Some(Action::PlayFromStart),
 
         (X, Char('a')) => Some(Action::AddToPlaylist),
         (C, Char('a')) => Some(Action::GoToAlbum),
@@ -226,7 +255,9 @@ fn handle_search_pane(key: &KeyEvent, state: &UiState) -> Option<Action> {
     }
 }
 
-fn handle_fullscreen(key: &KeyEvent) -> Option<Action> {
+fn handle_fullscreen(key: &KeyEvent, state: &UiState) -> Option<Action> {
+    let waveform = state.get_progress_display() == &ProgressDisplay::Waveform;
+
     let action = match (key.modifiers, key.code) {
         (X, Char(' ')) => Action::TogglePlayback,
 
@@ -235,16 +266,48 @@ fn handle_fullscreen(key: &KeyEvent) -> Option<Action> {
 
         (X, Char('p')) => Action::SeekBack(SEEK_SMALL),
         (S, Char('P')) => Action::SeekBack(SEEK_LARGE),
+        (X, Char('t')) => Action::SeekPopup,
+
+        // Jump to 0%, 10%, ... 90% of the track
+        (X, Char(c @ '0'..='9')) => Action::SeekToPercent((c as u8 - b'0') * 10),
+
+        (X, Char('=')) | (S, Char('+')) => Action::AdjustVolume(VOLUME_STEP),
+        (X, Char('-')) => Action::AdjustVolume(-VOLUME_STEP),
+        (X, Char('m')) => Action::ToggleMute,
+
+        (X, Char('.')) => Action::AdjustSpeed(SPEED_STEP),
+        (X, Char(',')) => Action::AdjustSpeed(-SPEED_STEP),
+        (X, Backspace) => Action::ResetSpeed,
+        (S, Char('R')) => Action::CycleRepeat,
+
+        (S, Char('(')) => Action::SetLoopStart,
+        (S, Char(')')) => Action::SetLoopEnd,
+        (X, Char('\\')) => Action::ClearLoop,
+
+        (X, Char('\'')) => Action::NextChapter,
+        (X, Char(';')) => Action::PrevChapter,
+        (S, Char('"')) => Action::ChapterList,
+
+        (S, Char('E')) => Action::CycleEqPreset(Incrementor::Down),
 
         (X, Char('w')) | (S, Char('W')) => Action::SetProgressDisplay(ProgressDisplay::Waveform),
         (X, Char('o')) | (S, Char('O')) => {
             Action::SetProgressDisplay(ProgressDisplay::Oscilloscope)
         }
         (X, Char('b')) | (S, Char('B')) => Action::SetProgressDisplay(ProgressDisplay::ProgressBar),
+        (X, Char('y')) | (S, Char('Y')) => Action::SetProgressDisplay(ProgressDisplay::Spectrum),
+        (X, Char('i')) | (S, Char('I')) => Action::SetProgressDisplay(state.next_stereo_display()),
 
         (S, Char('{')) => Action::IncrementWFSmoothness(Incrementor::Down),
         (S, Char('}')) => Action::IncrementWFSmoothness(Incrementor::Up),
 
+        // Zoom and pan only mean something to the waveform
+        (X, Char('k') | Up) if waveform => Action::ZoomWaveform(Incrementor::Up),
+        (X, Char('j') | Down) if waveform => Action::ZoomWaveform(Incrementor::Down),
+        (X, Char('l') | Right) if waveform => Action::PanWaveform(Incrementor::Up),
+        (X, Char('h') | Left) if waveform => Action::PanWaveform(Incrementor::Down),
+        (X, Char('c')) if waveform => Action::CenterWaveform,
+
         _ => Action::RevertFullscreen,
     };
 
@@ -256,6 +319,10 @@ fn handle_popup(key: &KeyEvent, popup: &PopupType) -> Option<Action> {
         PopupType::Settings(s) => root_manager(key, s),
         PopupType::Playlist(p) => handle_playlist(key, p),
         PopupType::ThemeManager => handle_themeing(key),
+        PopupType::SeekTo => handle_seek(key),
+        PopupType::Equalizer => handle_equalizer(key),
+        PopupType::SleepTimer => handle_sleep_timer(key),
+        PopupType::Chapters => handle_chapters(key),
         PopupType::Error(_) => Some(Action::ClosePopup),
         _ => None,
     }
@@ -267,6 +334,7 @@ fn root_manager(key: &KeyEvent, variant: &SettingsMode) -> Option<Action> {
         ViewRoots => match key.code {
             Char('a') => Some(Action::RootAdd),
             Char('d') => Some(Action::RootRemove),
+            Char('o') => Some(Action::OutputDevices),
             Up | Char('k') => Some(Action::PopupScrollUp),
             Down | Char('j') => Some(Action::PopupScrollDown),
             Char('`') => Some(Action::ClosePopup),
@@ -283,6 +351,14 @@ fn root_manager(key: &KeyEvent, variant: &SettingsMode) -> Option<Action> {
             Enter => Some(Action::RootConfirm),
             _ => None,
         },
+        OutputDevice => match key.code {
+            Up | Char('k') => Some(Action::PopupScrollUp),
+            Down | Char('j') => Some(Action::PopupScrollDown),
+            Enter => Some(Action::OutputDeviceConfirm),
+            Char('`') => Some(Action::ClosePopup),
+            Esc => Some(Action::ViewSettings),
+            _ => None,
+        },
     }
 }
 
@@ -328,6 +404,50 @@ fn handle_themeing(key: &KeyEvent) -> Option<Action> {
     }
 }
 
+fn handle_seek(key: &KeyEvent) -> Option<Action> {
+    match key.code {
+        Esc => Some(Action::ClosePopup),
+        Enter => Some(Action::SeekConfirm),
+        _ => Some(Action::PopupInput(*key)),
+    }
+}
+
+fn handle_equalizer(key: &KeyEvent) -> Option<Action> {
+    match (key.modifiers, key.code) {
+        (_, Up) | (X, Char('k')) => Some(Action::PopupScrollUp),
+        (_, Down) | (X, Char('j')) => Some(Action::PopupScrollDown),
+        (_, Left) | (X, Char('h')) => Some(Action::AdjustEqBand(-EQ_STEP)),
+        (_, Right) | (X, Char('l')) => Some(Action::AdjustEqBand(EQ_STEP)),
+        (X, Tab) | (S, Char('E')) => Some(Action::CycleEqPreset(Incrementor::Down)),
+        (_, BackTab) => Some(Action::CycleEqPreset(Incrementor::Up)),
+        (X, Char('r')) => Some(Action::RevertEqualizer),
+        (X, Esc) | (X, Char('e')) => Some(Action::ClosePopup),
+        _ => None,
+    }
+}
+
+fn handle_sleep_timer(key: &KeyEvent) -> Option<Action> {
+    match key.code {
+        Up | Char('k') => Some(Action::PopupScrollUp),
+        Down | Char('j') => Some(Action::PopupScrollDown),
+        Enter => Some(Action::SleepTimerConfirm),
+        Char('f') => Some(Action::ToggleSleepFade),
+        Char('c') => Some(Action::CancelSleepTimer),
+        Esc | Char('z') => Some(Action::ClosePopup),
+        _ => None,
+    }
+}
+
+fn handle_chapters(key: &KeyEvent) -> Option<Action> {
+    match key.code {
+        Up | Char('k') => Some(Action::PopupScrollUp),
+        Down | Char('j') => Some(Action::PopupScrollDown),
+        Enter => Some(Action::ChapterConfirm),
+        Esc | Char('"') => Some(Action::ClosePopup),
+        _ => None,
+    }
+}
+
 pub fn next_event() -> Result<Option<Event>> {
     match event::poll(REFRESH_RATE)? {
         true => Ok(Some(event::read()?)),
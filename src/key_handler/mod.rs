@@ -24,6 +24,11 @@ const C: KeyModifiers = KeyModifiers::CONTROL;
 
 const SEEK_SMALL: u64 = 5;
 const SEEK_LARGE: u64 = 30;
+const VOLUME_STEP: i8 = 5;
+// In tenths, i.e. 0.1x
+const SPEED_STEP: i8 = 1;
+// In dB
+const EQ_STEP: i8 = 1;
 const SCROLL_MID: usize = 5;
 const SCROLL_XTRA: usize = 20;
 const SIDEBAR_INCREMENT: isize = 1;
@@ -32,12 +37,44 @@ const SIDEBAR_INCREMENT: isize = 1;
 pub enum Action {
     // Player Controls
     Play,
+    PlayFromStart,
     Stop,
     TogglePlayback,
     PlayNext,
     PlayPrev,
     SeekForward(u64),
     SeekBack(u64),
+    SeekToPercent(u8),
+    SeekPopup,
+    SeekConfirm,
+    AdjustVolume(i8),
+    ToggleMute,
+    AdjustSpeed(i8),
+    ResetSpeed,
+    CycleRepeat,
+
+    // A-B Loop
+    SetLoopStart,
+    SetLoopEnd,
+    ClearLoop,
+
+    // Chapters
+    NextChapter,
+    PrevChapter,
+    ChapterList,
+    ChapterConfirm,
+
+    // Equalizer
+    OpenEqualizer,
+    CycleEqPreset(Incrementor),
+    AdjustEqBand(i8),
+    RevertEqualizer,
+
+    // Sleep Timer
+    SleepTimer,
+    SleepTimerConfirm,
+    CancelSleepTimer,
+    ToggleSleepFade,
 
     // Queue & Playlist Actions
     QueueSong,
@@ -55,6 +92,8 @@ pub enum Action {
 
     // Updating App State
     UpdateLibrary,
+    ToggleWaveformBatch,
+    CancelWaveformBatch,
     SendSearch,
     UpdateSearch(KeyEvent),
     SortColumnsNext,
@@ -88,6 +127,9 @@ pub enum Action {
     ThemeRefresh,
 
     IncrementWFSmoothness(Incrementor),
+    ZoomWaveform(Incrementor),
+    PanWaveform(Incrementor),
+    CenterWaveform,
     IncrementSidebarSize(isize),
 
     SetProgressDisplay(ProgressDisplay),
@@ -106,6 +148,8 @@ pub enum Action {
     RootAdd,
     RootRemove,
     RootConfirm,
+    OutputDevices,
+    OutputDeviceConfirm,
 
     HandleErrors,
     SoftReset,
@@ -20,6 +20,7 @@ use unicode_normalization::UnicodeNormalization;
 use xxhash_rust::xxh3::xxh3_64;
 
 pub mod app_core;
+pub mod config;
 pub mod database;
 pub mod key_handler;
 pub mod library;
@@ -28,6 +29,7 @@ pub mod player;
 pub mod tui;
 pub mod ui_state;
 
+pub use config::Config;
 pub use database::Database;
 pub use library::{Library, SimpleSong};
 pub use playback::PlaybackSession;
@@ -45,9 +47,13 @@ pub type SongMap = IndexMap<u64, Arc<SimpleSong>, BuildNoHashHasher<u64>>;
 
 // ~120fps
 pub const REFRESH_RATE: Duration = Duration::from_millis(8);
+// Enough for the clock and a creeping playhead when nothing is animating
+pub const SLOW_REFRESH_RATE: Duration = Duration::from_millis(100);
 pub const CONFIG_DIRECTORY: &'static str = "concertus";
 pub const THEME_DIRECTORY: &'static str = "themes";
+pub const EQ_DIRECTORY: &'static str = "equalizer";
 pub const DATABASE_FILENAME: &'static str = "concertus.db";
+pub const CONFIG_FILENAME: &'static str = "config.toml";
 
 /// Create a hash based on...
 ///  - date of last modification (millis)
@@ -108,6 +114,26 @@ pub fn get_readable_duration(duration: Duration, style: DurationStyle) -> String
     }
 }
 
+/// Parses `ss`, `mm:ss` or `h:mm:ss` into a duration
+pub fn parse_timestamp(s: &str) -> Option<Duration> {
+    let parts = s
+        .trim()
+        .split(':')
+        .map(|p| p.parse::<u64>().ok())
+        .collect::<Option<Vec<u64>>>()?;
+
+    let secs = match parts.as_slice() {
+        [secs] => *secs,
+        [mins, secs] if *secs < 60 => mins.checked_mul(60)?.checked_add(*secs)?,
+        [hours, mins, secs] if *mins < 60 && *secs < 60 => {
+            hours.checked_mul(3600)?.checked_add(mins * 60 + secs)?
+        }
+        _ => return None,
+    };
+
+    Some(Duration::from_secs(secs))
+}
+
 fn truncate_at_last_space(s: &str, limit: usize) -> String {
     if s.chars().count() <= limit {
         return s.to_string();
@@ -254,3 +280,49 @@ const PLAYLIST_IDEAS: [&str; 46] = [
     "Waiting for the Beat to Drop (Forever)",
     "When the World Pauses",
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_timestamps() {
+        let cases = [
+            ("0", Some(0)),
+            ("45", Some(45)),
+            ("125", Some(125)),
+            ("1:05", Some(65)),
+            ("01:05", Some(65)),
+            ("90:00", Some(5400)),
+            ("0:00", Some(0)),
+            ("1:02:03", Some(3723)),
+            ("12:00:59", Some(43259)),
+            ("  2:30 ", Some(150)),
+            // Out of range fields
+            ("1:60", None),
+            ("1:60:00", None),
+            ("1:00:60", None),
+            ("999999999999999999:00", None),
+            ("9999999999999999:00:00", None),
+            // Junk
+            ("", None),
+            (":", None),
+            ("1:", None),
+            (":30", None),
+            ("1::30", None),
+            ("1:2:3:4", None),
+            ("-1:30", None),
+            ("1.5", None),
+            ("1:3O", None),
+            ("abc", None),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(
+                parse_timestamp(input),
+                expected.map(Duration::from_secs),
+                "parsing {input:?}"
+            );
+        }
+    }
+}
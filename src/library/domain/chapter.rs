@@ -0,0 +1,495 @@
+use anyhow::{Result, bail};
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+    time::Duration,
+};
+
+/// Nero chapter starts are counted in 100ns units
+const CHPL_TIMESCALE: f64 = 10_000_000.0;
+/// Guards against corrupt sample tables
+const MAX_CHAPTERS: usize = 4096;
+
+#[derive(Clone)]
+pub struct Chapter {
+    pub title: String,
+    pub start: Duration,
+}
+
+impl Chapter {
+    /// Reads the chapter list of an mp4 container, i.e. m4a and m4b files.
+    /// Nero `chpl` lists are preferred over QuickTime chapter tracks, as both
+    /// are commonly written side by side.
+    pub fn read_mp4<P: AsRef<Path>>(path: P) -> Result<Vec<Chapter>> {
+        read_chapters(&mut File::open(path)?)
+    }
+}
+
+fn read_chapters<R: Read + Seek>(file: &mut R) -> Result<Vec<Chapter>> {
+    let moov = read_moov(file)?;
+
+    let mut chapters = match find_path(&moov, &[b"udta", b"chpl"]) {
+        Some(chpl) => parse_chpl(chpl),
+        None => read_chapter_track(file, &moov)?,
+    };
+
+    chapters.sort_by_key(|c| c.start);
+    chapters.dedup_by_key(|c| c.start);
+
+    Ok(chapters)
+}
+
+/// Steps over the top level boxes, skipping the (potentially huge) media
+/// data, and loads the movie header
+fn read_moov<R: Read + Seek>(file: &mut R) -> Result<Vec<u8>> {
+    let file_len = file.seek(SeekFrom::End(0))?;
+    let mut pos = 0;
+
+    while pos + 8 <= file_len {
+        file.seek(SeekFrom::Start(pos))?;
+
+        let mut header = [0u8; 8];
+        file.read_exact(&mut header)?;
+        let mut size = u32::from_be_bytes(header[..4].try_into()?) as u64;
+        let mut header_len = 8;
+
+        if size == 1 {
+            let mut large = [0u8; 8];
+            file.read_exact(&mut large)?;
+            size = u64::from_be_bytes(large);
+            header_len = 16;
+        } else if size == 0 {
+            size = file_len - pos;
+        }
+
+        if size < header_len || size > file_len - pos {
+            bail!("Malformed mp4 box");
+        }
+
+        if &header[4..] == b"moov" {
+            let mut moov = vec![0u8; (size - header_len) as usize];
+            file.read_exact(&mut moov)?;
+            return Ok(moov);
+        }
+
+        pos += size;
+    }
+
+    bail!("No moov box found")
+}
+
+/// Iterates the child boxes of a box body as `(kind, body)` pairs
+fn boxes(mut data: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    std::iter::from_fn(move || {
+        if data.len() < 8 {
+            return None;
+        }
+
+        let mut size = u32::from_be_bytes(data[..4].try_into().ok()?) as usize;
+        let kind = &data[4..8];
+        let mut header_len = 8;
+
+        if size == 1 {
+            size = u64::from_be_bytes(data.get(8..16)?.try_into().ok()?) as usize;
+            header_len = 16;
+        } else if size == 0 {
+            size = data.len();
+        }
+
+        if size < header_len || size > data.len() {
+            return None;
+        }
+
+        let body = &data[header_len..size];
+        data = &data[size..];
+        Some((kind, body))
+    })
+}
+
+fn find<'a>(data: &'a [u8], kind: &[u8; 4]) -> Option<&'a [u8]> {
+    boxes(data).find(|(k, _)| k == kind).map(|(_, body)| body)
+}
+
+fn find_path<'a>(data: &'a [u8], path: &[&[u8; 4]]) -> Option<&'a [u8]> {
+    path.iter().try_fold(data, |body, kind| find(body, kind))
+}
+
+fn be_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn be_u64(data: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(data.get(at..at + 8)?.try_into().ok()?))
+}
+
+fn parse_chpl(chpl: &[u8]) -> Vec<Chapter> {
+    let version = chpl.first().copied().unwrap_or_default();
+    // Version 1 carries four reserved bytes after the flags
+    let mut pos = if version == 1 { 8 } else { 4 };
+
+    let Some(&count) = chpl.get(pos) else {
+        return Vec::new();
+    };
+    pos += 1;
+
+    let mut chapters = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let Some(start) = be_u64(chpl, pos) else {
+            break;
+        };
+        let Some(&title_len) = chpl.get(pos + 8) else {
+            break;
+        };
+        let Some(title) = chpl.get(pos + 9..pos + 9 + title_len as usize) else {
+            break;
+        };
+        pos += 9 + title_len as usize;
+
+        chapters.push(Chapter {
+            title: String::from_utf8_lossy(title).trim().to_string(),
+            start: Duration::from_secs_f64(start as f64 / CHPL_TIMESCALE),
+        });
+    }
+
+    chapters
+}
+
+/// QuickTime chapters live in a text track, referenced by the audio track
+/// through `tref/chap`. Each sample is a length prefixed title.
+fn read_chapter_track<R: Read + Seek>(file: &mut R, moov: &[u8]) -> Result<Vec<Chapter>> {
+    let tracks = boxes(moov)
+        .filter(|(kind, _)| *kind == b"trak")
+        .map(|(_, body)| body)
+        .collect::<Vec<_>>();
+
+    let Some(chapter_id) = tracks
+        .iter()
+        .find_map(|trak| find_path(trak, &[b"tref", b"chap"]))
+        .and_then(|chap| be_u32(chap, 0))
+    else {
+        return Ok(Vec::new());
+    };
+
+    let Some(trak) = tracks
+        .iter()
+        .find(|trak| track_id(trak) == Some(chapter_id))
+    else {
+        return Ok(Vec::new());
+    };
+
+    let (Some(mdhd), Some(stbl)) = (
+        find_path(trak, &[b"mdia", b"mdhd"]),
+        find_path(trak, &[b"mdia", b"minf", b"stbl"]),
+    ) else {
+        return Ok(Vec::new());
+    };
+
+    let timescale = match mdhd.first() {
+        Some(1) => be_u32(mdhd, 20),
+        _ => be_u32(mdhd, 12),
+    }
+    .filter(|t| *t > 0)
+    .unwrap_or(1) as f64;
+
+    let starts = sample_starts(stbl);
+    let offsets = sample_offsets(stbl);
+
+    let mut chapters = Vec::with_capacity(starts.len());
+    for (start, offset) in starts.into_iter().zip(offsets) {
+        file.seek(SeekFrom::Start(offset))?;
+
+        let mut len = [0u8; 2];
+        file.read_exact(&mut len)?;
+        let mut title = vec![0u8; u16::from_be_bytes(len) as usize];
+        file.read_exact(&mut title)?;
+
+        chapters.push(Chapter {
+            title: String::from_utf8_lossy(&title).trim().to_string(),
+            start: Duration::from_secs_f64(start as f64 / timescale),
+        });
+    }
+
+    Ok(chapters)
+}
+
+fn track_id(trak: &[u8]) -> Option<u32> {
+    let tkhd = find(trak, b"tkhd")?;
+    match tkhd.first()? {
+        1 => be_u32(tkhd, 20),
+        _ => be_u32(tkhd, 12),
+    }
+}
+
+/// Start of every sample in the track's timescale, from the `stts` run lengths
+fn sample_starts(stbl: &[u8]) -> Vec<u64> {
+    let Some(stts) = find(stbl, b"stts") else {
+        return Vec::new();
+    };
+
+    let entries = be_u32(stts, 4).unwrap_or_default() as usize;
+    let mut starts = Vec::new();
+    let mut time = 0u64;
+
+    for idx in 0..entries {
+        let (Some(count), Some(delta)) = (be_u32(stts, 8 + idx * 8), be_u32(stts, 12 + idx * 8))
+        else {
+            break;
+        };
+
+        for _ in 0..count {
+            if starts.len() == MAX_CHAPTERS {
+                return starts;
+            }
+            starts.push(time);
+            time += delta as u64;
+        }
+    }
+
+    starts
+}
+
+/// File offset of every sample, walking the chunk layout described by
+/// `stsc`, `stsz` and `stco`/`co64`
+fn sample_offsets(stbl: &[u8]) -> Vec<u64> {
+    let chunk_offsets = match (find(stbl, b"stco"), find(stbl, b"co64")) {
+        (Some(stco), _) => (0..be_u32(stco, 4).unwrap_or_default() as usize)
+            .map_while(|idx| be_u32(stco, 8 + idx * 4).map(u64::from))
+            .collect(),
+        (None, Some(co64)) => (0..be_u32(co64, 4).unwrap_or_default() as usize)
+            .map_while(|idx| be_u64(co64, 8 + idx * 8))
+            .collect(),
+        (None, None) => Vec::<u64>::new(),
+    };
+
+    let (Some(stsz), Some(stsc)) = (find(stbl, b"stsz"), find(stbl, b"stsc")) else {
+        return Vec::new();
+    };
+
+    let fixed_size = be_u32(stsz, 4).unwrap_or_default();
+    let sample_count = (be_u32(stsz, 8).unwrap_or_default() as usize).min(MAX_CHAPTERS);
+    let sample_size = |idx: usize| match fixed_size {
+        0 => be_u32(stsz, 12 + idx * 4).unwrap_or_default() as u64,
+        size => size as u64,
+    };
+
+    // (first chunk, samples per chunk), chunks counted from 1
+    let runs = (0..be_u32(stsc, 4).unwrap_or_default() as usize)
+        .map_while(|idx| Some((be_u32(stsc, 8 + idx * 12)?, be_u32(stsc, 12 + idx * 12)?)))
+        .collect::<Vec<_>>();
+
+    let mut offsets = Vec::with_capacity(sample_count);
+    for (chunk_idx, chunk_offset) in chunk_offsets.iter().enumerate() {
+        let per_chunk = runs
+            .iter()
+            .take_while(|(first, _)| *first as usize <= chunk_idx + 1)
+            .last()
+            .map(|(_, n)| *n)
+            .unwrap_or_default();
+
+        let mut offset = *chunk_offset;
+        for _ in 0..per_chunk {
+            if offsets.len() == sample_count {
+                return offsets;
+            }
+            offsets.push(offset);
+            offset += sample_size(offsets.len() - 1);
+        }
+    }
+
+    offsets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn mp4_box(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut out = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+        out.extend_from_slice(kind);
+        out.extend_from_slice(body);
+        out
+    }
+
+    fn full_box(kind: &[u8; 4], fields: &[u32]) -> Vec<u8> {
+        let mut body = vec![0u8; 4];
+        fields.iter().for_each(|f| body.extend(f.to_be_bytes()));
+        mp4_box(kind, &body)
+    }
+
+    fn chpl(chapters: &[(u64, &str)]) -> Vec<u8> {
+        // Version 1, flags, reserved
+        let mut body = vec![1, 0, 0, 0, 0, 0, 0, 0, chapters.len() as u8];
+        for (start, title) in chapters {
+            body.extend(start.to_be_bytes());
+            body.push(title.len() as u8);
+            body.extend(title.as_bytes());
+        }
+        mp4_box(b"chpl", &body)
+    }
+
+    fn read(file: Vec<u8>) -> Result<Vec<(String, Duration)>> {
+        let chapters = read_chapters(&mut Cursor::new(file))?;
+        Ok(chapters.into_iter().map(|c| (c.title, c.start)).collect())
+    }
+
+    fn ftyp() -> Vec<u8> {
+        mp4_box(b"ftyp", b"M4A \0\0\0\0")
+    }
+
+    #[test]
+    fn reads_chpl_list() {
+        let moov = mp4_box(
+            b"moov",
+            &mp4_box(
+                b"udta",
+                &chpl(&[(900_000_000, " Second "), (0, "First"), (0, "Duplicate")]),
+            ),
+        );
+        let file = [ftyp(), mp4_box(b"mdat", &[0; 64]), moov].concat();
+
+        assert_eq!(
+            read(file).unwrap(),
+            vec![
+                ("First".to_string(), Duration::ZERO),
+                ("Second".to_string(), Duration::from_secs(90)),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_chapter_track() {
+        let titles = ["Intro", "Outro"];
+        let samples = titles
+            .iter()
+            .flat_map(|t| {
+                [
+                    (t.len() as u16).to_be_bytes().to_vec(),
+                    t.as_bytes().to_vec(),
+                ]
+            })
+            .flatten()
+            .collect::<Vec<u8>>();
+
+        let head = [ftyp(), mp4_box(b"mdat", &samples)].concat();
+        let first_sample = (head.len() - samples.len()) as u32;
+
+        let audio = mp4_box(
+            b"trak",
+            &[
+                full_box(b"tkhd", &[0, 0, 1]),
+                mp4_box(b"tref", &mp4_box(b"chap", &2u32.to_be_bytes())),
+            ]
+            .concat(),
+        );
+
+        let stbl = [
+            // Two samples, 30s apart
+            full_box(b"stts", &[1, 2, 30_000]),
+            full_box(b"stsz", &[0, 2, 7, 7]),
+            full_box(b"stsc", &[1, 1, 2, 1]),
+            full_box(b"stco", &[1, first_sample]),
+        ]
+        .concat();
+        let text = mp4_box(
+            b"trak",
+            &[
+                full_box(b"tkhd", &[0, 0, 2]),
+                mp4_box(
+                    b"mdia",
+                    &[
+                        full_box(b"mdhd", &[0, 0, 1000]),
+                        mp4_box(b"minf", &mp4_box(b"stbl", &stbl)),
+                    ]
+                    .concat(),
+                ),
+            ]
+            .concat(),
+        );
+
+        let file = [head, mp4_box(b"moov", &[audio, text].concat())].concat();
+
+        assert_eq!(
+            read(file).unwrap(),
+            vec![
+                ("Intro".to_string(), Duration::ZERO),
+                ("Outro".to_string(), Duration::from_secs(30)),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_64_bit_box_sizes() {
+        let mut mdat = 1u32.to_be_bytes().to_vec();
+        mdat.extend(b"mdat");
+        mdat.extend(32u64.to_be_bytes());
+        mdat.extend([0; 16]);
+
+        let moov = mp4_box(b"moov", &mp4_box(b"udta", &chpl(&[(0, "Only")])));
+        let file = [ftyp(), mdat, moov].concat();
+
+        assert_eq!(read(file).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn rejects_oversized_top_level_box() {
+        let mut mdat = mp4_box(b"mdat", &[0; 16]);
+        mdat[..4].copy_from_slice(&1000u32.to_be_bytes());
+
+        assert!(read([ftyp(), mdat].concat()).is_err());
+    }
+
+    #[test]
+    fn rejects_undersized_top_level_box() {
+        let mut ftyp = ftyp();
+        ftyp[..4].copy_from_slice(&4u32.to_be_bytes());
+
+        assert!(read(ftyp).is_err());
+    }
+
+    #[test]
+    fn rejects_file_without_moov() {
+        assert!(read(ftyp()).is_err());
+        assert!(read(Vec::new()).is_err());
+    }
+
+    #[test]
+    fn ignores_oversized_child_box() {
+        let mut udta = mp4_box(b"udta", &chpl(&[(0, "First")]));
+        udta[..4].copy_from_slice(&500u32.to_be_bytes());
+        let file = [ftyp(), mp4_box(b"moov", &udta)].concat();
+
+        assert!(read(file).unwrap().is_empty());
+    }
+
+    #[test]
+    fn keeps_chapters_before_truncated_chpl_entry() {
+        let mut list = chpl(&[(0, "First"), (10_000_000, "Second")]);
+        // Cut the second title short, keeping the box size consistent
+        list.truncate(list.len() - 3);
+        let len = list.len() as u32;
+        list[..4].copy_from_slice(&len.to_be_bytes());
+
+        let file = [ftyp(), mp4_box(b"moov", &mp4_box(b"udta", &list))].concat();
+
+        assert_eq!(
+            read(file).unwrap(),
+            vec![("First".to_string(), Duration::ZERO)]
+        );
+    }
+
+    #[test]
+    fn caps_chapter_track_sample_count() {
+        let stbl = [
+            full_box(b"stts", &[1, u32::MAX, 1]),
+            full_box(b"stsz", &[2, u32::MAX]),
+            full_box(b"stsc", &[1, 1, u32::MAX, 1]),
+            full_box(b"stco", &[1, 0]),
+        ]
+        .concat();
+
+        assert_eq!(sample_starts(&stbl).len(), MAX_CHAPTERS);
+        assert_eq!(sample_offsets(&stbl).len(), MAX_CHAPTERS);
+    }
+}
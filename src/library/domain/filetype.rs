@@ -6,7 +6,7 @@ use std::fmt::Display;
 
 pub static LEGAL_EXTENSION: std::sync::LazyLock<std::collections::HashSet<&'static str>> =
     std::sync::LazyLock::new(|| {
-        std::collections::HashSet::from(["mp3", "m4a", "flac", "ogg", "wav", "opus"])
+        std::collections::HashSet::from(["mp3", "m4a", "m4b", "flac", "ogg", "wav", "opus"])
     });
 
 #[allow(clippy::upper_case_acronyms)]
@@ -26,7 +26,7 @@ impl From<&str> for FileType {
     fn from(str: &str) -> Self {
         match str {
             "mp3" => Self::MP3,
-            "aac" | "m4a" => Self::M4A,
+            "aac" | "m4a" | "m4b" => Self::M4A,
             "ogg" => Self::OGG,
             "wav" => Self::WAV,
             "flac" => Self::FLAC,
@@ -1,4 +1,4 @@
-use super::{FileType, SongInfo};
+use super::{Chapter, FileType, ReplayGain, SongInfo};
 use crate::{
     calculate_signature, database::Database, get_readable_duration, normalize_metadata_str as nms,
 };
@@ -31,6 +31,8 @@ pub struct LongSong {
     pub(crate) sample_rate: Option<u32>,
     pub(crate) filetype: FileType,
     pub(crate) path: PathBuf,
+    pub(crate) replay_gain: ReplayGain,
+    pub(crate) chapters: Vec<Chapter>,
 }
 
 impl LongSong {
@@ -101,6 +103,16 @@ impl LongSong {
 
             song_info.track_no = tag.track();
             song_info.disc_no = tag.disk();
+
+            song_info.replay_gain = ReplayGain::from_tag(tag);
+        }
+
+        if song_info.filetype == FileType::M4A {
+            song_info.chapters = Chapter::read_mp4(path)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|c| c.start < song_info.duration)
+                .collect();
         }
 
         Ok(song_info)
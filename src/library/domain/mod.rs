@@ -1,14 +1,20 @@
 mod album;
+mod chapter;
 mod filetype;
 mod long_song;
 mod playlist;
+mod replay_gain;
 mod simple_song;
+mod waveform;
 
 pub use album::Album;
+pub use chapter::Chapter;
 pub use filetype::{FileType, LEGAL_EXTENSION};
 pub use long_song::LongSong;
 pub use playlist::{Playlist, PlaylistSong};
+pub use replay_gain::ReplayGain;
 pub use simple_song::SimpleSong;
+pub use waveform::WaveformPeaks;
 
 pub trait SongInfo {
     fn get_id(&self) -> u64;
@@ -23,6 +29,7 @@ pub trait SongInfo {
 pub trait SongDatabase {
     fn get_path(&self) -> anyhow::Result<String>;
     fn update_play_count(&self) -> anyhow::Result<()>;
-    fn get_waveform(&self) -> anyhow::Result<Vec<f32>>;
-    fn set_waveform_db(&self, wf: &[f32]) -> anyhow::Result<()>;
+    fn get_waveform(&self) -> anyhow::Result<WaveformPeaks>;
+    fn set_waveform_db(&self, wf: &WaveformPeaks) -> anyhow::Result<()>;
+    fn get_replay_gain(&self) -> anyhow::Result<ReplayGain>;
 }
@@ -0,0 +1,35 @@
+use lofty::tag::{ItemKey, Tag};
+
+/// Loudness normalization values written by a ReplayGain scanner. Gains are
+/// in dB, peaks are the largest sample amplitude where 1.0 is full scale.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ReplayGain {
+    pub track_gain: Option<f32>,
+    pub track_peak: Option<f32>,
+    pub album_gain: Option<f32>,
+    pub album_peak: Option<f32>,
+}
+
+impl ReplayGain {
+    pub fn from_tag(tag: &Tag) -> Self {
+        let read = |key: ItemKey| tag.get_string(&key).and_then(parse_value);
+
+        ReplayGain {
+            track_gain: read(ItemKey::ReplayGainTrackGain),
+            track_peak: read(ItemKey::ReplayGainTrackPeak),
+            album_gain: read(ItemKey::ReplayGainAlbumGain),
+            album_peak: read(ItemKey::ReplayGainAlbumPeak),
+        }
+    }
+}
+
+// Gains are usually written as "-6.54 dB", peaks as a bare "0.988831"
+fn parse_value(s: &str) -> Option<f32> {
+    let s = s.trim();
+    let s = s
+        .strip_suffix("dB")
+        .or_else(|| s.strip_suffix("db"))
+        .unwrap_or(s);
+
+    s.trim().parse::<f32>().ok().filter(|v| v.is_finite())
+}
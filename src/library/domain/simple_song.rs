@@ -1,4 +1,4 @@
-use super::{FileType, SongInfo};
+use super::{FileType, ReplayGain, SongInfo, WaveformPeaks};
 use crate::{Database, get_readable_duration};
 use anyhow::Result;
 use std::{sync::Arc, time::Duration};
@@ -33,17 +33,23 @@ impl super::SongDatabase for SimpleSong {
     }
 
     /// Retrieve the waveform of a song
-    /// returns Result<Vec<f32>>
-    fn get_waveform(&self) -> Result<Vec<f32>> {
+    /// returns Result<WaveformPeaks>
+    fn get_waveform(&self) -> Result<WaveformPeaks> {
         let mut db = Database::open()?;
         db.get_waveform(self.id)
     }
 
     /// Store the waveform of a song in the databse
-    fn set_waveform_db(&self, wf: &[f32]) -> Result<()> {
+    fn set_waveform_db(&self, wf: &WaveformPeaks) -> Result<()> {
         let mut db = Database::open()?;
         db.set_waveform(self.id, wf)
     }
+
+    /// Retrieve the ReplayGain values stored at scan time
+    fn get_replay_gain(&self) -> Result<ReplayGain> {
+        let mut db = Database::open()?;
+        db.get_replay_gain(self.id)
+    }
 }
 
 /// Generic getter methods
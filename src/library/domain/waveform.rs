@@ -0,0 +1,231 @@
+use anyhow::{Result, bail};
+
+/// Points in the coarsest level, enough to span the whole track at once
+const WAVEFORM_BASE_LEN: usize = 512;
+/// Finer levels are added until there are about this many points for every
+/// second of audio
+const POINTS_PER_SECOND: f32 = 20.0;
+/// Caps the finest level at 16384 points, for hour long mixes
+const MAX_LEVELS: usize = 6;
+
+/// Min & max amplitude of a track at several resolutions. Every level has
+/// twice the points of the one before it, the coarsest coming first. Values
+/// are stored as a u8 each, where 0 and 255 stand for -1.0 and 1.0.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WaveformPeaks {
+    levels: Vec<Vec<(f32, f32)>>,
+}
+
+impl WaveformPeaks {
+    /// Builds every level from mono `samples`, scaled so that the loudest
+    /// peak reaches full scale
+    pub fn from_samples(samples: &[f32], sample_rate: u32) -> Self {
+        let ranges = samples.iter().map(|&s| (s, s)).collect::<Vec<_>>();
+        Self::from_ranges(&ranges, sample_rate)
+    }
+
+    /// Like `from_samples`, for audio already reduced to the min & max of
+    /// every `1 / rate` seconds
+    pub fn from_ranges(ranges: &[(f32, f32)], rate: u32) -> Self {
+        let seconds = ranges.len() as f32 / rate.max(1) as f32;
+        let wanted = seconds * POINTS_PER_SECOND / WAVEFORM_BASE_LEN as f32;
+        let count = (wanted.max(1.0).log2().round() as usize + 1).min(MAX_LEVELS);
+
+        let len = WAVEFORM_BASE_LEN << (count - 1);
+        let mut finest = (0..len)
+            .map(|idx| {
+                let chunk = &ranges[idx * ranges.len() / len..(idx + 1) * ranges.len() / len];
+                chunk
+                    .iter()
+                    .copied()
+                    .reduce(|(min, max), (lo, hi)| (min.min(lo), max.max(hi)))
+                    // Very short files leave some chunks empty
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+
+        let loudest = finest
+            .iter()
+            .map(|(min, max)| min.abs().max(max.abs()))
+            .fold(0.0, f32::max);
+
+        if loudest > f32::EPSILON {
+            finest.iter_mut().for_each(|(min, max)| {
+                *min = (*min / loudest).clamp(-1.0, 1.0);
+                *max = (*max / loudest).clamp(-1.0, 1.0);
+            });
+        }
+
+        let mut levels = vec![finest];
+        while levels[0].len() > WAVEFORM_BASE_LEN {
+            let coarser = levels[0]
+                .chunks_exact(2)
+                .map(|pair| (pair[0].0.min(pair[1].0), pair[0].1.max(pair[1].1)))
+                .collect();
+            levels.insert(0, coarser);
+        }
+
+        WaveformPeaks { levels }
+    }
+
+    pub fn levels(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Points in the finest level
+    pub fn resolution(&self) -> usize {
+        self.levels.last().map_or(0, Vec::len)
+    }
+
+    /// The part of the track between `start` and `end` (both fractions of
+    /// the track), at the coarsest level which still has `min_points` in it
+    pub fn window(&self, start: f32, end: f32, min_points: usize) -> &[(f32, f32)] {
+        let span = (end - start).clamp(0.0, 1.0);
+
+        let Some(level) = self
+            .levels
+            .iter()
+            .find(|level| level.len() as f32 * span >= min_points as f32)
+            .or(self.levels.last())
+        else {
+            return &[];
+        };
+
+        let first = ((start.clamp(0.0, 1.0) * level.len() as f32) as usize).min(level.len());
+        let last = ((end.clamp(0.0, 1.0) * level.len() as f32).ceil() as usize).max(first);
+
+        &level[first..last.min(level.len())]
+    }
+
+    pub fn to_blob(&self) -> Vec<u8> {
+        self.levels
+            .iter()
+            .flatten()
+            .flat_map(|(min, max)| [quantize(*min), quantize(*max)])
+            .collect()
+    }
+
+    /// `levels` is the count stored alongside the blob, which fixes the
+    /// length every level must have
+    pub fn from_blob(levels: usize, blob: &[u8]) -> Result<Self> {
+        if levels == 0
+            || levels > MAX_LEVELS
+            || blob.len() != WAVEFORM_BASE_LEN * ((1 << levels) - 1) * 2
+        {
+            bail!("Stored waveform doesn't match its resolution");
+        }
+
+        let mut pairs = blob
+            .chunks_exact(2)
+            .map(|pair| (dequantize(pair[0]), dequantize(pair[1])));
+
+        let levels = (0..levels)
+            .map(|level| pairs.by_ref().take(WAVEFORM_BASE_LEN << level).collect())
+            .collect();
+
+        Ok(WaveformPeaks { levels })
+    }
+}
+
+fn quantize(value: f32) -> u8 {
+    ((value.clamp(-1.0, 1.0) + 1.0) * 127.5).round() as u8
+}
+
+fn dequantize(value: u8) -> f32 {
+    value as f32 / 127.5 - 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A sine sweeping up in loudness, with a single spike partway through
+    fn peaks(seconds: usize, rate: u32) -> WaveformPeaks {
+        let len = seconds * rate as usize;
+        let mut samples = (0..len)
+            .map(|idx| (idx as f32 * 0.3).sin() * idx as f32 / len as f32 * 0.5)
+            .collect::<Vec<_>>();
+        samples[len / 3] = 0.9;
+
+        WaveformPeaks::from_samples(&samples, rate)
+    }
+
+    #[test]
+    fn adds_levels_for_longer_tracks() {
+        assert_eq!(peaks(10, 100).levels(), 1);
+        assert_eq!(peaks(240, 100).levels(), 4);
+        assert_eq!(peaks(240, 100).resolution(), 4096);
+        assert_eq!(peaks(4 * 3600, 10).levels(), MAX_LEVELS);
+    }
+
+    #[test]
+    fn keeps_the_loudest_peak_at_full_scale() {
+        let peaks = peaks(240, 100);
+        let coarsest = peaks.window(0.0, 1.0, 1);
+
+        assert_eq!(coarsest.len(), WAVEFORM_BASE_LEN);
+        assert_eq!(coarsest[WAVEFORM_BASE_LEN / 3].1, 1.0);
+        assert!(coarsest.iter().all(|(min, max)| min <= max));
+    }
+
+    #[test]
+    fn merges_ranges_into_coarser_levels() {
+        // Just long enough for a second level, at 20 ranges a second
+        let ranges = [(-0.2, 0.1), (-0.1, 0.4)].repeat(WAVEFORM_BASE_LEN);
+        let peaks = WaveformPeaks::from_ranges(&ranges, 20);
+
+        assert_eq!(peaks.levels(), 2);
+        assert!(peaks.window(0.0, 1.0, 1).iter().all(|p| *p == (-0.5, 1.0)));
+    }
+
+    #[test]
+    fn round_trips_through_blob() {
+        let peaks = peaks(240, 100);
+        let blob = peaks.to_blob();
+        let restored = WaveformPeaks::from_blob(peaks.levels(), &blob).unwrap();
+
+        assert_eq!(restored.levels(), peaks.levels());
+        assert_eq!(restored.to_blob(), blob);
+
+        for (a, b) in peaks
+            .levels
+            .iter()
+            .flatten()
+            .zip(restored.levels.iter().flatten())
+        {
+            assert!((a.0 - b.0).abs() < 1.0 / 127.5);
+            assert!((a.1 - b.1).abs() < 1.0 / 127.5);
+        }
+    }
+
+    #[test]
+    fn rejects_blob_of_wrong_length() {
+        let blob = peaks(240, 100).to_blob();
+
+        assert!(WaveformPeaks::from_blob(3, &blob).is_err());
+        assert!(WaveformPeaks::from_blob(4, &blob[1..]).is_err());
+        assert!(WaveformPeaks::from_blob(0, &[]).is_err());
+        assert!(WaveformPeaks::from_blob(64, &blob).is_err());
+    }
+
+    #[test]
+    fn window_picks_coarsest_level_with_enough_points() {
+        // Levels of 512, 1024, 2048 and 4096 points
+        let peaks = peaks(240, 100);
+        let level = |idx: usize| peaks.levels[idx].as_slice();
+
+        assert_eq!(peaks.window(0.0, 1.0, 500), level(0));
+        assert_eq!(peaks.window(0.0, 0.5, 500), &level(1)[..512]);
+        assert_eq!(peaks.window(0.5, 0.75, 500), &level(2)[1024..1536]);
+        assert_eq!(peaks.window(0.25, 0.375, 500), &level(3)[1024..1536]);
+    }
+
+    #[test]
+    fn window_falls_back_to_finest_level() {
+        let peaks = peaks(240, 100);
+
+        assert_eq!(peaks.window(0.0, 0.01, 500).len(), 41);
+        assert_eq!(peaks.window(0.5, 0.5, 500).len(), 0);
+        assert_eq!(peaks.window(1.0, 1.5, 1).len(), 0);
+    }
+}
@@ -114,6 +114,8 @@ impl Library {
             self.db.delete_songs(&removed_ids)?;
         }
 
+        self.backfill_tags()?;
+
         Ok((new_file_count, removed_ids.len()))
     }
 
@@ -167,6 +169,24 @@ impl Library {
             .collect()
     }
 
+    /// Reads the tags of songs flagged by a database migration again, which
+    /// a rescan alone never would as their hashes are unchanged
+    fn backfill_tags(&mut self) -> Result<()> {
+        let flagged = self.db.get_rescan_paths()?;
+        if flagged.is_empty() {
+            return Ok(());
+        }
+
+        // Files which can't be read stay flagged, they're most likely gone
+        // and will be dropped by the next scan
+        let songs = flagged
+            .into_par_iter()
+            .filter_map(|(id, path)| Some((id, LongSong::build_song_lofty(&path).ok()?)))
+            .collect::<Vec<_>>();
+
+        self.db.update_rescanned(&songs)
+    }
+
     fn process_songs(paths: Vec<PathBuf>) -> Vec<LongSong> {
         paths
             .into_par_iter()
@@ -339,6 +359,8 @@ impl Library {
             }
         }
 
+        self.backfill_tags()?;
+
         let _ = tx.send(LibraryRefreshProgress::UpdatingDatabase {
             progress: REMOVALS_FINISHED,
         });
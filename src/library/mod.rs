@@ -3,6 +3,7 @@ mod library;
 
 pub use domain::LEGAL_EXTENSION;
 pub use domain::{
-    Album, FileType, LongSong, Playlist, PlaylistSong, SimpleSong, SongDatabase, SongInfo,
+    Album, Chapter, FileType, LongSong, Playlist, PlaylistSong, ReplayGain, SimpleSong,
+    SongDatabase, SongInfo, WaveformPeaks,
 };
 pub use library::Library;
@@ -1,5 +1,6 @@
 fn main() -> anyhow::Result<()> {
     unsafe { std::env::set_var("RUST_BACKTRACE", "1") };
-    concertus::app_core::Concertus::new().run()?;
+    let config = concertus::Config::load(std::env::args().skip(1))?;
+    concertus::app_core::Concertus::new(config).run()?;
     Ok(())
 }
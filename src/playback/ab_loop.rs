@@ -0,0 +1,16 @@
+use std::time::Duration;
+
+/// A section of the current track to repeat. The loop only runs once both
+/// points are set.
+#[derive(Clone, Copy, PartialEq)]
+pub struct AbLoop {
+    pub a: Duration,
+    pub b: Option<Duration>,
+}
+
+impl AbLoop {
+    /// Start and end of the loop, once it is complete
+    pub fn region(&self) -> Option<(Duration, Duration)> {
+        self.b.map(|b| (self.a, b))
+    }
+}
@@ -1,9 +1,15 @@
+mod ab_loop;
+mod repeat;
 mod session;
+mod sleep_timer;
 mod validated_song;
 
 pub const HISTORY_CAPACITY: usize = 50;
 
+pub use ab_loop::AbLoop;
+pub use repeat::RepeatMode;
 pub use session::PlaybackSession;
+pub use sleep_timer::{SLEEP_FADE, SleepTarget, SleepTimer};
 pub use validated_song::ValidatedSong;
 
 use std::sync::Arc;
@@ -0,0 +1,43 @@
+/// What plays once the current track finishes
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum RepeatMode {
+    #[default]
+    Off,
+    /// Replays the current track
+    One,
+    /// Finished songs go back to the end of the queue
+    Queue,
+    /// Loops the album or playlist playback was started from
+    Context,
+}
+
+impl RepeatMode {
+    pub fn from_str(s: &str) -> Self {
+        match s {
+            "one" => Self::One,
+            "queue" => Self::Queue,
+            "context" => Self::Context,
+            _ => Self::Off,
+        }
+    }
+
+    pub fn cycle(self) -> Self {
+        match self {
+            Self::Off => Self::One,
+            Self::One => Self::Queue,
+            Self::Queue => Self::Context,
+            Self::Context => Self::Off,
+        }
+    }
+}
+
+impl std::fmt::Display for RepeatMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RepeatMode::Off => write!(f, "off"),
+            RepeatMode::One => write!(f, "one"),
+            RepeatMode::Queue => write!(f, "queue"),
+            RepeatMode::Context => write!(f, "context"),
+        }
+    }
+}
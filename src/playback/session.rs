@@ -1,13 +1,16 @@
 use crate::{
     Database, SongMap,
-    library::SimpleSong,
-    playback::{HISTORY_CAPACITY, QueueDelta, ValidatedSong},
+    library::{SimpleSong, SongInfo},
+    playback::{
+        AbLoop, HISTORY_CAPACITY, QueueDelta, RepeatMode, SleepTarget, SleepTimer, ValidatedSong,
+    },
 };
-use anyhow::Result;
+use anyhow::{Result, bail};
 use rand::seq::SliceRandom;
 use std::{
     collections::{HashSet, VecDeque},
     sync::Arc,
+    time::{Duration, Instant},
 };
 
 pub struct PlaybackSession {
@@ -16,6 +19,11 @@ pub struct PlaybackSession {
     queue_ids: HashSet<u64>,
 
     now_playing: Option<Arc<SimpleSong>>,
+    repeat: RepeatMode,
+    // The album or playlist playback was started from
+    context: Vec<Arc<SimpleSong>>,
+    sleep_timer: Option<SleepTimer>,
+    ab_loop: Option<AbLoop>,
 }
 
 impl PlaybackSession {
@@ -25,6 +33,10 @@ impl PlaybackSession {
             history: VecDeque::with_capacity(HISTORY_CAPACITY),
             queue_ids: HashSet::new(),
             now_playing: None,
+            repeat: RepeatMode::Off,
+            context: Vec::new(),
+            sleep_timer: None,
+            ab_loop: None,
         }
     }
 
@@ -103,19 +115,62 @@ impl PlaybackSession {
     /// Take now_playing, put to history.
     pub fn advance(&mut self) -> (QueueDelta, Option<Arc<ValidatedSong>>) {
         let prev = self.get_head();
+        let finished = self.now_playing.take();
 
-        if let Some(current) = self.now_playing.take() {
-            self.push_history(&current);
+        if let Some(current) = &finished {
+            self.push_history(current);
+
+            if self.repeat == RepeatMode::Queue
+                && let Ok(song) = ValidatedSong::new(current)
+            {
+                self.queue_ids.insert(song.id());
+                self.queue.push_back(song);
+            }
         }
 
-        let next = self.queue.pop_front().map(|song| {
-            self.remove_id_if_final(song.id());
-            song
-        });
+        let next = match self.queue.pop_front() {
+            Some(song) => {
+                self.remove_id_if_final(song.id());
+                Some(song)
+            }
+            None if self.repeat == RepeatMode::Context => self
+                .context_after(finished.as_ref())
+                .and_then(|s| ValidatedSong::new(s).ok()),
+            None => None,
+        };
 
         (self.head_delta(prev), next)
     }
 
+    /// Moves on from a track which played to the end by itself. Skipping by
+    /// hand goes through `advance`, so repeat-one never traps the user.
+    pub fn finish_track(&mut self) -> (QueueDelta, Option<Arc<ValidatedSong>>) {
+        if self.repeat == RepeatMode::One
+            && let Some(current) = self.now_playing.take()
+        {
+            self.push_history(&current);
+            return (QueueDelta::HeadUnchanged, ValidatedSong::new(&current).ok());
+        }
+
+        self.advance()
+    }
+
+    /// Called when the current track finishes on its own. Unlike
+    /// `finish_track`, a due sleep timer ends the session here, leaving the
+    /// queue untouched for next time.
+    pub fn end_track(&mut self) -> (QueueDelta, Option<Arc<ValidatedSong>>) {
+        if !self.stops_after_current() {
+            return self.finish_track();
+        }
+
+        self.sleep_timer = None;
+        if let Some(current) = self.now_playing.take() {
+            self.push_history(&current);
+        }
+
+        (QueueDelta::HeadUnchanged, None)
+    }
+
     pub fn remove_from_queue(&mut self, idx: usize) -> (QueueDelta, Option<Arc<ValidatedSong>>) {
         let prev = self.get_head();
         let dropped = self.queue.remove(idx).map(|s| {
@@ -146,6 +201,10 @@ impl PlaybackSession {
         self.queue_ids.contains(&id)
     }
 
+    pub fn queue_ids(&self) -> Vec<u64> {
+        self.queue.iter().map(|s| s.id()).collect()
+    }
+
     pub fn queue_len(&self) -> usize {
         self.queue.len()
     }
@@ -165,6 +224,25 @@ impl PlaybackSession {
         Ok(())
     }
 
+    /// Refills the queue from the last session, handing back the track which
+    /// was playing and how far in it was
+    pub(crate) fn load_session(
+        &mut self,
+        song_map: &SongMap,
+    ) -> Result<Option<(Arc<ValidatedSong>, Duration)>> {
+        let mut db = Database::open()?;
+        let session = db.import_session(song_map)?;
+
+        self.enqueue_multi(&session.queue)?;
+
+        let resume = session
+            .now_playing
+            .and_then(|song| ValidatedSong::new(&song).ok())
+            .map(|song| (song, session.elapsed));
+
+        Ok(resume)
+    }
+
     pub fn push_history(&mut self, song: &Arc<SimpleSong>) {
         self.history.push_front(Arc::clone(song));
         if self.history.len() > HISTORY_CAPACITY {
@@ -197,6 +275,178 @@ impl PlaybackSession {
         Ok(Some((delta, validated_popped)))
     }
 
+    // ======================
+    //    REPEAT
+    // ======================
+
+    pub fn repeat(&self) -> RepeatMode {
+        self.repeat
+    }
+
+    pub fn set_repeat(&mut self, mode: RepeatMode) {
+        self.repeat = mode
+    }
+
+    pub fn set_context(&mut self, songs: Vec<Arc<SimpleSong>>) {
+        self.context = songs
+    }
+
+    /// The song which plays once the current one finishes, taking the
+    /// repeat mode into account
+    pub fn up_next(&self) -> Option<&Arc<SimpleSong>> {
+        if self.repeat == RepeatMode::One {
+            return self.now_playing.as_ref();
+        }
+
+        self.peek_queue().or_else(|| match self.repeat {
+            RepeatMode::Queue => self.now_playing.as_ref(),
+            RepeatMode::Context => self.context_after(self.now_playing.as_ref()),
+            _ => None,
+        })
+    }
+
+    /// `up_next`, ready for the player. Queued songs are validated already.
+    pub fn up_next_validated(&self) -> Option<Arc<ValidatedSong>> {
+        match (self.repeat, self.queue.front()) {
+            (RepeatMode::One, _) | (_, None) => {
+                self.up_next().and_then(|s| ValidatedSong::new(s).ok())
+            }
+            (_, Some(head)) => Some(Arc::clone(head)),
+        }
+    }
+
+    /// True when what plays next hinges on the current track rather than on
+    /// the queue alone, so the player needs a new follow up on each track
+    pub fn next_follows_current(&self) -> bool {
+        self.sleep_timer.is_some()
+            || match self.repeat {
+                RepeatMode::Off => false,
+                RepeatMode::One => true,
+                RepeatMode::Queue | RepeatMode::Context => self.queue.is_empty(),
+            }
+    }
+
+    /// The context entry after `current`, wrapping around. Songs from outside
+    /// the context lead back to its start.
+    fn context_after(&self, current: Option<&Arc<SimpleSong>>) -> Option<&Arc<SimpleSong>> {
+        let idx = current
+            .and_then(|c| self.context.iter().position(|s| s.id == c.id))
+            .map_or(0, |i| (i + 1) % self.context.len());
+
+        self.context.get(idx)
+    }
+
+    // ======================
+    //    SLEEP TIMER
+    // ======================
+
+    pub fn sleep_timer(&self) -> Option<&SleepTimer> {
+        self.sleep_timer.as_ref()
+    }
+
+    pub fn sleep_timer_mut(&mut self) -> Option<&mut SleepTimer> {
+        self.sleep_timer.as_mut()
+    }
+
+    pub fn set_sleep_timer(&mut self, timer: Option<SleepTimer>) {
+        self.sleep_timer = timer;
+    }
+
+    /// A timed sleep counts down from the moment it is set, so with nothing
+    /// playing it would only run out unheard and cut the next playback short
+    pub fn arm_sleep_timer(&mut self, timer: SleepTimer, playing: bool) -> Result<()> {
+        if !playing && matches!(timer.target, SleepTarget::At(_)) {
+            bail!("Start playback before setting a timed sleep");
+        }
+
+        self.sleep_timer = Some(timer);
+        Ok(())
+    }
+
+    /// Forget a timed sleep which ran out while nothing was playing, rather
+    /// than have it end whatever plays next
+    pub fn drop_expired_sleep_timer(&mut self) {
+        let expired = match self.sleep_timer.as_ref().map(|t| t.target) {
+            Some(SleepTarget::At(deadline)) => Instant::now() >= deadline,
+            _ => false,
+        };
+
+        if expired {
+            self.sleep_timer = None;
+        }
+    }
+
+    /// True when playback should end along with the current track, in which
+    /// case the player must not be handed a gapless follow up
+    pub fn stops_after_current(&self) -> bool {
+        let (Some(timer), Some(current)) = (&self.sleep_timer, &self.now_playing) else {
+            return false;
+        };
+
+        match timer.target {
+            SleepTarget::At(deadline) => Instant::now() >= deadline,
+            SleepTarget::EndOfTrack => true,
+            SleepTarget::EndOfAlbum => self
+                .peek_queue()
+                .is_none_or(|next| next.album_id != current.album_id),
+        }
+    }
+
+    /// Time left before the sleep timer ends playback, given how far into
+    /// the current track we are
+    pub fn sleep_remaining(&self, elapsed: Duration) -> Option<Duration> {
+        let timer = self.sleep_timer.as_ref()?;
+
+        if let SleepTarget::At(deadline) = timer.target {
+            return Some(deadline.saturating_duration_since(Instant::now()));
+        }
+
+        let current = self.now_playing.as_ref()?;
+        let mut remaining = current.get_duration().saturating_sub(elapsed);
+
+        if timer.target == SleepTarget::EndOfAlbum {
+            remaining += self
+                .queue
+                .iter()
+                .take_while(|s| s.meta.album_id == current.album_id)
+                .map(|s| s.meta.get_duration())
+                .sum::<Duration>();
+        }
+
+        Some(remaining)
+    }
+
+    // ======================
+    //    A-B LOOP
+    // ======================
+
+    pub fn ab_loop(&self) -> Option<&AbLoop> {
+        self.ab_loop.as_ref()
+    }
+
+    /// Setting A after B drops the old B, the loop can't run backwards
+    pub fn set_loop_start(&mut self, pos: Duration) -> &AbLoop {
+        let b = self.ab_loop.and_then(|l| l.b).filter(|b| *b > pos);
+        self.ab_loop.insert(AbLoop { a: pos, b })
+    }
+
+    pub fn set_loop_end(&mut self, pos: Duration) -> Result<&AbLoop> {
+        let Some(ab_loop) = self.ab_loop.as_mut() else {
+            bail!("Set point A before point B");
+        };
+
+        if pos <= ab_loop.a {
+            bail!("Point B must come after point A");
+        }
+
+        ab_loop.b = Some(pos);
+        Ok(ab_loop)
+    }
+
+    pub fn clear_loop(&mut self) {
+        self.ab_loop = None
+    }
+
     // ======================
     //    INTERNAL METHODS
     // ======================
@@ -222,3 +472,49 @@ impl PlaybackSession {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timed(deadline: Instant) -> SleepTimer {
+        SleepTimer::new(SleepTarget::At(deadline), false)
+    }
+
+    #[test]
+    fn timed_sleep_refused_while_stopped() {
+        let mut session = PlaybackSession::init();
+        let deadline = Instant::now() + Duration::from_secs(600);
+
+        assert!(session.arm_sleep_timer(timed(deadline), false).is_err());
+        assert!(session.sleep_timer().is_none());
+
+        session.arm_sleep_timer(timed(deadline), true).unwrap();
+        assert!(session.sleep_timer().is_some());
+    }
+
+    #[test]
+    fn track_sleep_allowed_while_stopped() {
+        let mut session = PlaybackSession::init();
+        let timer = SleepTimer::new(SleepTarget::EndOfTrack, false);
+
+        session.arm_sleep_timer(timer, false).unwrap();
+        assert!(session.sleep_timer().is_some());
+    }
+
+    #[test]
+    fn expired_sleep_dropped() {
+        let mut session = PlaybackSession::init();
+        let now = Instant::now();
+
+        session.arm_sleep_timer(timed(now), true).unwrap();
+        session.drop_expired_sleep_timer();
+        assert!(session.sleep_timer().is_none());
+
+        session
+            .arm_sleep_timer(timed(now + Duration::from_secs(600)), true)
+            .unwrap();
+        session.drop_expired_sleep_timer();
+        assert!(session.sleep_timer().is_some());
+    }
+}
@@ -0,0 +1,52 @@
+use std::time::{Duration, Instant};
+
+/// Length of the optional volume fade before the timer ends playback
+pub const SLEEP_FADE: Duration = Duration::from_secs(15);
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SleepTarget {
+    /// A fixed point in time, wherever the current track happens to be. This
+    /// is wall-clock time, so the countdown carries on while paused. The
+    /// track based targets hold still along with playback.
+    At(Instant),
+    EndOfTrack,
+    EndOfAlbum,
+}
+
+pub struct SleepTimer {
+    pub target: SleepTarget,
+    pub fade: bool,
+    // Fade level last handed to the player, so it isn't flooded with commands
+    fade_level: f32,
+}
+
+impl SleepTimer {
+    pub fn new(target: SleepTarget, fade: bool) -> Self {
+        SleepTimer {
+            target,
+            fade,
+            fade_level: 1.0,
+        }
+    }
+
+    /// Volume scale for the time left, 1.0 until the fade begins
+    pub fn fade_level(&self, remaining: Duration) -> f32 {
+        match self.fade && remaining < SLEEP_FADE {
+            true => remaining.as_secs_f32() / SLEEP_FADE.as_secs_f32(),
+            false => 1.0,
+        }
+    }
+
+    /// Returns the new level only when it moved far enough to be audible
+    pub fn update_fade(&mut self, remaining: Duration) -> Option<f32> {
+        let level = (self.fade_level(remaining) * 100.0).round() / 100.0;
+
+        match level != self.fade_level {
+            true => {
+                self.fade_level = level;
+                Some(level)
+            }
+            false => None,
+        }
+    }
+}
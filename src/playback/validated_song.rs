@@ -1,6 +1,6 @@
 use crate::{
     Database, get_readable_duration,
-    library::{SimpleSong, SongDatabase, SongInfo},
+    library::{ReplayGain, SimpleSong, SongDatabase, SongInfo, WaveformPeaks},
 };
 use anyhow::Result;
 use std::{path::PathBuf, sync::Arc, time::Duration};
@@ -8,6 +8,7 @@ use std::{path::PathBuf, sync::Arc, time::Duration};
 pub struct ValidatedSong {
     pub meta: Arc<SimpleSong>,
     pub path: String,
+    pub replay_gain: ReplayGain,
 }
 
 impl ValidatedSong {
@@ -19,6 +20,7 @@ impl ValidatedSong {
         Ok(Arc::new(Self {
             meta: Arc::clone(&song),
             path,
+            replay_gain: song.get_replay_gain().unwrap_or_default(),
         }))
     }
 
@@ -79,15 +81,19 @@ impl SongDatabase for ValidatedSong {
     }
 
     /// Retrieve the waveform of a song
-    /// returns Result<Vec<f32>>
-    fn get_waveform(&self) -> Result<Vec<f32>> {
+    /// returns Result<WaveformPeaks>
+    fn get_waveform(&self) -> Result<WaveformPeaks> {
         let mut db = Database::open()?;
         db.get_waveform(self.id())
     }
 
     /// Store the waveform of a song in the databse
-    fn set_waveform_db(&self, wf: &[f32]) -> Result<()> {
+    fn set_waveform_db(&self, wf: &WaveformPeaks) -> Result<()> {
         let mut db = Database::open()?;
         db.set_waveform(self.id(), wf)
     }
+
+    fn get_replay_gain(&self) -> Result<ReplayGain> {
+        Ok(self.replay_gain)
+    }
 }
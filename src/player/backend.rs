@@ -1,14 +1,29 @@
-use anyhow::Result;
+use anyhow::{Result, bail};
 use std::{path::Path, time::Duration};
 
+use crate::player::EqSettings;
+
 pub(super) trait ConcertusBackend: Send + 'static {
-    fn play(&mut self, path: &Path) -> Result<()>;
+    /// `gain` is the linear ReplayGain adjustment for this track alone
+    fn play(&mut self, path: &Path, gain: f32) -> Result<()>;
+    /// Like `play`, but left paused at `pos` without any of the track
+    /// reaching the output
+    fn cue(&mut self, path: &Path, gain: f32, pos: Duration) -> Result<()>;
     fn stop(&mut self);
     fn pause(&mut self);
     fn resume(&mut self);
 
-    fn seek_back(&mut self, secs: u64) -> Result<()>;
-    fn seek_forward(&mut self, secs: u64) -> Result<()>;
+    fn seek_to(&mut self, pos: Duration) -> Result<()>;
+
+    fn seek_back(&mut self, secs: u64) -> Result<()> {
+        let target = self.position().saturating_sub(Duration::from_secs(secs));
+        self.seek_to(target)
+    }
+
+    fn seek_forward(&mut self, secs: u64) -> Result<()> {
+        let target = self.position() + Duration::from_secs(secs);
+        self.seek_to(target)
+    }
 
     // State queries
     fn position(&self) -> Duration;
@@ -21,13 +36,58 @@ pub(super) trait ConcertusBackend: Send + 'static {
         false
     }
 
-    fn set_next(&mut self, _path: &Path) -> Result<()> {
+    fn set_next(&mut self, _path: &Path, _gain: f32) -> Result<()> {
         Ok(()) // silently succeed if not supported
     }
 
-    // fn clear_queue(&mut self) {}
+    fn clear_next(&mut self) {}
+
+    /// Overlap the end of the current track with the one from `set_next`
+    fn set_crossfade(&mut self, _dur: Duration) {}
+
+    /// Linear amplitude, where 1.0 leaves samples untouched
+    fn set_volume(&mut self, _gain: f32) {}
+
+    /// Playback rate, which must not change the pitch
+    fn set_speed(&mut self, _speed: f32) -> Result<()> {
+        bail!("Changing playback speed requires the native engine")
+    }
+
+    fn set_equalizer(&mut self, _settings: &EqSettings) {}
+
+    /// Reopen output on the named device, or the default with `None`. The
+    /// current track may be dropped, the caller is expected to restart it.
+    fn set_output_device(&mut self, _name: Option<&str>) -> Result<()> {
+        Ok(())
+    }
+
+    /// An error raised by the output stream since the last call. Once the
+    /// device is lost the stream is assumed dead, and reopened with
+    /// `set_output_device`.
+    fn take_stream_error(&mut self) -> Option<OutputError> {
+        None
+    }
+}
+
+/// An error raised by a running output stream
+pub(super) enum OutputError {
+    /// The device has gone away, taking the stream with it
+    DeviceLost(String),
+    /// Anything else, which playback carries on through
+    Other(String),
+}
+
+impl OutputError {
+    /// Stores `err` in `slot`, unless a lost device is still waiting there
+    /// to be dealt with
+    pub(super) fn record(slot: &mut Option<OutputError>, err: cpal::StreamError) {
+        if matches!(slot, Some(OutputError::DeviceLost(_))) {
+            return;
+        }
 
-    fn drain_samples(&mut self) -> Vec<f32> {
-        Vec::new()
+        *slot = Some(match err {
+            cpal::StreamError::DeviceNotAvailable => OutputError::DeviceLost(err.to_string()),
+            _ => OutputError::Other(err.to_string()),
+        });
     }
 }
@@ -1,25 +1,27 @@
-use crate::player::{ConcertusBackend, OSCILLO_BUFFER_CAPACITY};
+use crate::player::{AudioTap, ConcertusBackend, EqSettings, backend::OutputError, cplayback};
 use anyhow::Result;
-use std::{path::Path, time::Duration};
+use crossbeam_channel::Sender;
+use std::{path::Path, sync::Arc, time::Duration};
 
 pub struct ConcertusEngine {
     engine: cplayback::Player,
 }
 
 impl ConcertusEngine {
-    pub fn new() -> Result<Self> {
+    pub fn new(waker: Sender<()>, tap: Arc<AudioTap>) -> Result<Self> {
         Ok(Self {
-            engine: cplayback::Player::new()?,
+            engine: cplayback::Player::new(waker, tap)?,
         })
     }
 }
 
 impl ConcertusBackend for ConcertusEngine {
-    fn play(&mut self, song: &Path) -> Result<()> {
-        self.engine.clear();
-        self.engine.play_blocking(song)?;
+    fn play(&mut self, song: &Path, gain: f32) -> Result<()> {
+        self.engine.play(song, gain)
+    }
 
-        Ok(())
+    fn cue(&mut self, song: &Path, gain: f32, pos: Duration) -> Result<()> {
+        self.engine.cue(song, gain, pos)
     }
 
     fn pause(&mut self) {
@@ -31,22 +33,11 @@ impl ConcertusBackend for ConcertusEngine {
     }
 
     fn stop(&mut self) {
-        let _ = self.engine.stop();
-    }
-
-    fn seek_forward(&mut self, secs: u64) -> Result<()> {
-        let elapsed = self.engine.position();
-
-        let new_time = Duration::from_secs(secs) + elapsed;
-        self.engine.seek(new_time)?;
-        Ok(())
+        self.engine.stop();
     }
 
-    fn seek_back(&mut self, secs: u64) -> Result<()> {
-        let elapsed = self.engine.position();
-        let new_time = elapsed.saturating_sub(Duration::from_secs(secs));
-        self.engine.seek(new_time)?;
-        Ok(())
+    fn seek_to(&mut self, pos: Duration) -> Result<()> {
+        self.engine.seek(pos)
     }
 
     fn position(&self) -> Duration {
@@ -69,12 +60,37 @@ impl ConcertusBackend for ConcertusEngine {
         true
     }
 
-    fn set_next(&mut self, song: &Path) -> Result<()> {
-        self.engine.queue(&song)?;
+    fn set_next(&mut self, song: &Path, gain: f32) -> Result<()> {
+        self.engine.queue(song, gain)?;
+        Ok(())
+    }
+
+    fn clear_next(&mut self) {
+        self.engine.clear_queue();
+    }
+
+    fn set_crossfade(&mut self, dur: Duration) {
+        self.engine.set_crossfade(dur);
+    }
+
+    fn set_volume(&mut self, gain: f32) {
+        self.engine.set_volume(gain);
+    }
+
+    fn set_speed(&mut self, speed: f32) -> Result<()> {
+        self.engine.set_speed(speed);
         Ok(())
     }
 
-    fn drain_samples(&mut self) -> Vec<f32> {
-        self.engine.tap.latest(OSCILLO_BUFFER_CAPACITY)
+    fn set_equalizer(&mut self, settings: &EqSettings) {
+        self.engine.set_equalizer(settings);
+    }
+
+    fn set_output_device(&mut self, name: Option<&str>) -> Result<()> {
+        self.engine.set_output_device(name)
+    }
+
+    fn take_stream_error(&mut self) -> Option<OutputError> {
+        self.engine.take_stream_error()
     }
 }
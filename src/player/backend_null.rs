@@ -0,0 +1,97 @@
+use crate::player::ConcertusBackend;
+use anyhow::{Result, bail};
+use lofty::{file::AudioFile, read_from_path};
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// Produces no audio. Position advances on the wall clock so that the rest
+/// of the player behaves exactly as it would with a real output device.
+#[derive(Default)]
+pub struct NullBackend {
+    loaded: bool,
+    duration: Option<Duration>,
+    // Position at the last pause/seek, plus the instant playback continued from it
+    offset: Duration,
+    resumed_at: Option<Instant>,
+}
+
+impl NullBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ConcertusBackend for NullBackend {
+    fn play(&mut self, path: &Path, _gain: f32) -> Result<()> {
+        if !path.exists() {
+            bail!("File not found: {}", path.display());
+        }
+
+        // Tracks without a readable duration simply play until stopped
+        self.duration = read_from_path(path)
+            .ok()
+            .map(|file| file.properties().duration());
+
+        self.loaded = true;
+        self.offset = Duration::ZERO;
+        self.resumed_at = Some(Instant::now());
+
+        Ok(())
+    }
+
+    fn cue(&mut self, path: &Path, gain: f32, pos: Duration) -> Result<()> {
+        self.play(path, gain)?;
+        self.resumed_at = None;
+        self.offset = pos;
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        *self = Self::default();
+    }
+
+    fn pause(&mut self) {
+        self.offset = self.position();
+        self.resumed_at = None;
+    }
+
+    fn resume(&mut self) {
+        if self.loaded {
+            self.resumed_at = Some(Instant::now());
+        }
+    }
+
+    fn seek_to(&mut self, pos: Duration) -> Result<()> {
+        self.offset = pos;
+        if self.resumed_at.is_some() {
+            self.resumed_at = Some(Instant::now());
+        }
+        Ok(())
+    }
+
+    fn position(&self) -> Duration {
+        let pos = match self.resumed_at {
+            Some(instant) => self.offset + instant.elapsed(),
+            None => self.offset,
+        };
+
+        match self.duration {
+            Some(dur) => pos.min(dur),
+            None => pos,
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        self.loaded && self.resumed_at.is_none()
+    }
+
+    fn is_stopped(&self) -> bool {
+        !self.loaded || self.track_ended()
+    }
+
+    fn track_ended(&self) -> bool {
+        self.loaded && self.duration.is_some_and(|dur| self.position() >= dur)
+    }
+}
@@ -1,8 +1,8 @@
 use anyhow::Result;
+use crossbeam_channel::Sender;
 use rodio::decoder::builder::SeekMode;
 use rodio::{ChannelCount, Decoder, OutputStream, OutputStreamBuilder, Sink, Source};
 use std::{
-    collections::VecDeque,
     fs::File,
     io::BufReader,
     num::NonZero,
@@ -14,44 +14,88 @@ use std::{
     time::Duration,
 };
 
-use crate::player::ConcertusBackend;
+use crate::player::{
+    AudioTap, ConcertusBackend, EqSettings,
+    backend::OutputError,
+    equalizer::{EqControl, Equalizer},
+    output_device::find_output_device,
+};
 
 pub struct RodioBackend {
     pub sink: Sink,
     duration: Option<Duration>,
     track_ended: Arc<AtomicBool>,
     _stream: OutputStream,
-    sample_buffer: Arc<Mutex<VecDeque<f32>>>,
+    tap: Arc<AudioTap>,
+    eq: Arc<EqControl>,
+    stream_error: Arc<Mutex<Option<OutputError>>>,
+    waker: Sender<()>,
 }
 
 impl RodioBackend {
-    pub fn new() -> Result<Self> {
-        let stream = OutputStreamBuilder::open_default_stream()?;
+    /// `waker` is poked whenever a track runs dry or the stream fails.
+    /// Everything played is copied into `tap`.
+    pub fn new(waker: Sender<()>, tap: Arc<AudioTap>) -> Result<Self> {
+        let stream_error = Arc::new(Mutex::new(None));
+        let stream = open_stream(None, &stream_error, &waker)?;
         let sink = Sink::connect_new(stream.mixer());
         let duration = None;
 
         Ok(Self {
             sink,
             _stream: stream,
-            sample_buffer: Arc::new(Mutex::new(VecDeque::with_capacity(8192))),
+            tap,
             track_ended: Arc::new(AtomicBool::new(false)),
+            eq: Arc::new(EqControl::default()),
+            stream_error,
+            waker,
             duration,
         })
     }
 }
 
-impl ConcertusBackend for RodioBackend {
-    fn play(&mut self, song: &Path) -> Result<()> {
-        let source = decode(song)?;
+impl RodioBackend {
+    fn load(&mut self, song: &Path, gain: f32) -> Result<impl Source<Item = f32> + Send + 'static> {
+        let source = decode(song)?.amplify(gain);
 
         self.track_ended.store(true, Ordering::SeqCst);
         let tapped = TappedSource::new(
             source,
-            Arc::clone(&self.sample_buffer),
+            Arc::clone(&self.tap),
             Arc::clone(&self.track_ended),
+            Arc::clone(&self.eq),
+            self.waker.clone(),
         );
 
         self.duration = tapped.total_duration();
+        Ok(tapped)
+    }
+}
+
+/// Rodio's default error callback prints to stderr, which would garble the
+/// UI, so errors are stored for the player thread to pick up instead
+fn open_stream(
+    name: Option<&str>,
+    errors: &Arc<Mutex<Option<OutputError>>>,
+    waker: &Sender<()>,
+) -> Result<OutputStream> {
+    let errors = Arc::clone(errors);
+    let waker = waker.clone();
+    let stream = OutputStreamBuilder::from_device(find_output_device(name)?)?
+        .with_error_callback(move |err| {
+            if let Ok(mut slot) = errors.lock() {
+                OutputError::record(&mut slot, err);
+            }
+            let _ = waker.try_send(());
+        })
+        .open_stream_or_fallback()?;
+
+    Ok(stream)
+}
+
+impl ConcertusBackend for RodioBackend {
+    fn play(&mut self, song: &Path, gain: f32) -> Result<()> {
+        let tapped = self.load(song, gain)?;
 
         self.sink.clear();
         self.sink.append(tapped);
@@ -60,6 +104,17 @@ impl ConcertusBackend for RodioBackend {
         Ok(())
     }
 
+    fn cue(&mut self, song: &Path, gain: f32, pos: Duration) -> Result<()> {
+        let mut tapped = self.load(song, gain)?;
+        tapped.try_seek(pos)?;
+
+        // Clearing leaves the sink paused
+        self.sink.clear();
+        self.sink.append(tapped);
+
+        Ok(())
+    }
+
     fn pause(&mut self) {
         self.sink.pause();
     }
@@ -72,25 +127,15 @@ impl ConcertusBackend for RodioBackend {
         self.sink.stop();
     }
 
-    fn seek_forward(&mut self, secs: u64) -> Result<()> {
-        let elapsed = self.position();
-        let new_time = Duration::from_secs(secs) + elapsed;
-
+    fn seek_to(&mut self, pos: Duration) -> Result<()> {
         if let Some(dur) = self.duration {
-            if new_time > dur {
+            if pos > dur {
                 self.stop();
                 return Ok(());
             }
         }
 
-        self.sink.try_seek(new_time)?;
-        Ok(())
-    }
-
-    fn seek_back(&mut self, secs: u64) -> Result<()> {
-        let elapsed = self.sink.get_pos();
-        self.sink
-            .try_seek(elapsed.saturating_sub(Duration::from_secs(secs)))?;
+        self.sink.try_seek(pos)?;
         Ok(())
     }
 
@@ -110,11 +155,32 @@ impl ConcertusBackend for RodioBackend {
         self.track_ended.load(Ordering::SeqCst) && self.sink.empty()
     }
 
-    fn drain_samples(&mut self) -> Vec<f32> {
-        self.sample_buffer
-            .lock()
-            .map(|mut s| s.make_contiguous().to_vec())
-            .unwrap_or_default()
+    fn set_volume(&mut self, gain: f32) {
+        self.sink.set_volume(gain);
+    }
+
+    fn set_equalizer(&mut self, settings: &EqSettings) {
+        self.eq.store(settings);
+    }
+
+    fn set_output_device(&mut self, name: Option<&str>) -> Result<()> {
+        let stream = open_stream(name, &self.stream_error, &self.waker)?;
+        self.take_stream_error();
+
+        let volume = self.sink.volume();
+        self.sink.stop();
+        self.sink = Sink::connect_new(stream.mixer());
+        self.sink.set_volume(volume);
+
+        // Dropping a stream logs to stderr by default, which would garble the UI
+        self._stream.log_on_drop(false);
+        self._stream = stream;
+
+        Ok(())
+    }
+
+    fn take_stream_error(&mut self) -> Option<OutputError> {
+        self.stream_error.lock().ok()?.take()
     }
 }
 
@@ -146,18 +212,42 @@ fn decode(song: &Path) -> Result<Decoder<BufReader<File>>> {
     Ok(builder.build()?)
 }
 
+/// Runs the equalizer over the decoded samples, then copies them into the
+/// audio tap on their way to the sink
 pub struct TappedSource<I> {
     input: I,
-    buffer: Arc<Mutex<VecDeque<f32>>>,
+    tap: Arc<AudioTap>,
+    // The tap only takes whole frames
+    frame: Vec<f32>,
     ended: Arc<AtomicBool>,
+    eq: Equalizer,
+    eq_control: Arc<EqControl>,
+    waker: Sender<()>,
+    channel: usize,
 }
 
-impl<I> TappedSource<I> {
-    pub fn new(input: I, buffer: Arc<Mutex<VecDeque<f32>>>, ended: Arc<AtomicBool>) -> Self {
+impl<I> TappedSource<I>
+where
+    I: Source<Item = f32>,
+{
+    pub fn new(
+        input: I,
+        tap: Arc<AudioTap>,
+        ended: Arc<AtomicBool>,
+        eq_control: Arc<EqControl>,
+        waker: Sender<()>,
+    ) -> Self {
+        let eq = Equalizer::new(input.sample_rate().get(), input.channels().get() as usize);
+
         TappedSource {
             input,
-            buffer,
+            tap,
+            frame: Vec::with_capacity(8),
             ended,
+            eq,
+            eq_control,
+            waker,
+            channel: 0,
         }
     }
 }
@@ -171,16 +261,25 @@ where
     fn next(&mut self) -> Option<Self::Item> {
         match self.input.next() {
             Some(sample) => {
-                if let Ok(mut samples) = self.buffer.try_lock() {
-                    if samples.len() >= super::OSCILLO_BUFFER_CAPACITY {
-                        samples.pop_front();
-                    }
-                    samples.push_back(sample);
+                if self.channel == 0 {
+                    self.eq.sync(&self.eq_control);
+                }
+                let sample = self.eq.process(self.channel, sample);
+                let channels = self.input.channels().get() as usize;
+                self.channel = (self.channel + 1) % channels;
+
+                self.frame.push(sample);
+                if self.channel == 0 {
+                    self.tap
+                        .set_format(channels, self.input.sample_rate().get());
+                    self.tap.push(&self.frame);
+                    self.frame.clear();
                 }
                 Some(sample)
             }
             None => {
                 self.ended.store(true, Ordering::SeqCst);
+                let _ = self.waker.try_send(());
                 None
             }
         }
@@ -1,68 +1,178 @@
 use crate::{
+    Config, REFRESH_RATE,
     player::{
-        track::ConcertusTrack, ConcertusBackend, PlaybackMetrics, PlaybackState, PlayerCommand,
-        PlayerEvent, OSCILLO_BUFFER_CAPACITY,
+        ConcertusBackend, ENDING_SOON, EqSettings, EventBus, MAX_SPEED, MAX_VOLUME, MIN_SPEED,
+        PlaybackMetrics, PlaybackState, PlayerCommand, PlayerEvent, ReplayGainConfig,
+        backend::OutputError, track::ConcertusTrack,
     },
-    REFRESH_RATE,
 };
-use crossbeam_channel::{Receiver, Sender};
+use crossbeam_channel::{Receiver, select};
 use std::{
     sync::Arc,
     thread::{self, JoinHandle},
+    time::{Duration, Instant},
 };
 
+const OUTPUT_RETRY: Duration = Duration::from_secs(2);
+
+/// Where playback stood when the output stream died
+struct LostOutput {
+    elapsed: Duration,
+    paused: bool,
+    retry_at: Instant,
+    reported: bool,
+}
+
 pub struct PlayerCore {
     backend: Box<dyn ConcertusBackend>,
     commands: Receiver<PlayerCommand>,
-    events: Sender<PlayerEvent>,
+    /// Poked by the backend when a track finishes or the output fails
+    wake: Receiver<()>,
+    events: EventBus,
     metrics: Arc<PlaybackMetrics>,
+    replaygain: ReplayGainConfig,
+    crossfade: Duration,
+    fade: f32,
 
     current: Option<ConcertusTrack>,
     next: Option<ConcertusTrack>,
+    ab_loop: Option<(Duration, Duration)>,
+    ending_soon_sent: bool,
+    display_visible: bool,
+    /// Position as of the previous pass, as the metrics extrapolate theirs
+    last_position: Duration,
+
+    output_device: Option<String>,
+    lost_output: Option<LostOutput>,
 }
 
 impl PlayerCore {
     pub fn spawn(
         backend: Box<dyn ConcertusBackend>,
+        config: &Config,
         commands: Receiver<PlayerCommand>,
-        events: Sender<PlayerEvent>,
+        wake: Receiver<()>,
+        events: EventBus,
         metrics: Arc<PlaybackMetrics>,
     ) -> JoinHandle<()> {
+        let replaygain = config.replaygain;
+        let crossfade = config.crossfade();
+
         thread::spawn(move || {
             let mut core = PlayerCore {
                 backend,
                 commands,
+                wake,
                 events,
                 metrics,
+                replaygain,
+                crossfade,
+                fade: 1.0,
 
                 current: None,
                 next: None,
+                ab_loop: None,
+                ending_soon_sent: false,
+                display_visible: true,
+                last_position: Duration::ZERO,
+
+                output_device: None,
+                lost_output: None,
             };
 
             core.run();
         })
     }
 
+    /// Sleeps until a command comes in, the backend reports something, or
+    /// the next deadline from `next_wake` is up
     fn run(&mut self) {
         loop {
+            let timer = match self.next_wake() {
+                Some(timeout) => crossbeam_channel::after(timeout),
+                None => crossbeam_channel::never(),
+            };
+
+            select! {
+                recv(self.commands) -> cmd => match cmd {
+                    Ok(cmd) => self.handle_command(cmd),
+                    // The handle is gone, and the app along with it
+                    Err(_) => return,
+                },
+                recv(self.wake) -> _ => {}
+                recv(timer) -> _ => {}
+            }
+
             self.process_commands();
+            self.check_output();
             self.check_track_end();
+            self.check_loop();
+            self.check_ending_soon();
             self.update_metrics();
-            thread::sleep(REFRESH_RATE);
         }
     }
 
+    /// Polls at the refresh rate only while the position is on screen.
+    /// Otherwise the thread wakes just in time for the end of the track, the
+    /// ending soon notice or the loop's B point, and not at all when paused
+    /// or stopped.
+    fn next_wake(&self) -> Option<Duration> {
+        if let Some(lost) = &self.lost_output {
+            return Some(lost.retry_at.saturating_duration_since(Instant::now()));
+        }
+
+        let duration = self.current.as_ref()?.duration();
+        if self.backend.is_paused() {
+            return None;
+        }
+
+        // Unknown lengths leave nothing to time the end by
+        if self.display_visible || duration.is_zero() {
+            return Some(REFRESH_RATE);
+        }
+
+        let pos = self.backend.position();
+        let mut next = duration.saturating_sub(pos);
+
+        if !self.ending_soon_sent {
+            next = next.min(duration.saturating_sub(ENDING_SOON).saturating_sub(pos));
+        }
+
+        if let Some((_, b)) = self.ab_loop
+            && pos < b
+        {
+            next = next.min(b - pos);
+        }
+
+        Some(next.div_f32(self.metrics.get_speed()).max(REFRESH_RATE))
+    }
+
     fn process_commands(&mut self) {
         while let Ok(cmd) = self.commands.try_recv() {
-            match cmd {
-                PlayerCommand::Play(s) => self.play_song(s),
-                PlayerCommand::SetNext(s) => self.set_next(s),
-                PlayerCommand::ClearNext => self.clear_next(),
-                PlayerCommand::TogglePlayback => self.toggle_playback(),
-                PlayerCommand::Stop => self.stop(),
-                PlayerCommand::SeekForward(x) => self.seek_forward(x),
-                PlayerCommand::SeekBack(x) => self.seek_back(x),
-            }
+            self.handle_command(cmd);
+        }
+    }
+
+    fn handle_command(&mut self, cmd: PlayerCommand) {
+        match cmd {
+            PlayerCommand::Play(s, start) => self.play_song(s, start),
+            PlayerCommand::Cue(s, pos) => self.cue_song(s, pos),
+            PlayerCommand::SetNext(s) => self.set_next(s),
+            PlayerCommand::ClearNext => self.clear_next(),
+            PlayerCommand::TogglePlayback => self.toggle_playback(),
+            PlayerCommand::Stop => self.stop(),
+            PlayerCommand::SeekForward(x) => self.seek_forward(x),
+            PlayerCommand::SeekBack(x) => self.seek_back(x),
+            PlayerCommand::SeekTo(pos) => self.seek_to(pos),
+            PlayerCommand::SetVolume(v) => self.set_volume(v),
+            PlayerCommand::AdjustVolume(d) => self.adjust_volume(d),
+            PlayerCommand::ToggleMute => self.toggle_mute(),
+            PlayerCommand::SetSpeed(x) => self.set_speed(x),
+            PlayerCommand::SetEqualizer(eq) => self.set_equalizer(eq),
+            PlayerCommand::SetOutputDevice(d) => self.set_output_device(d),
+            PlayerCommand::SetFade(f) => self.set_fade(f),
+            PlayerCommand::SetLoop(l) => self.ab_loop = l,
+            PlayerCommand::SetDisplayVisible(v) => self.display_visible = v,
         }
     }
 
@@ -72,63 +182,194 @@ impl PlayerCore {
             match self.next.take() {
                 // GAPLESS BRANCH
                 Some(next) => {
+                    self.ab_loop = None;
                     self.current = Some(next.clone());
                     self.emit(PlayerEvent::TrackStarted((next, true)));
                 }
                 // STANDARD BRANCH
                 None => {
                     self.current = None;
+                    self.ab_loop = None;
                     self.emit(PlayerEvent::PlaybackStopped);
                 }
             }
         }
     }
 
-    fn update_metrics(&mut self) {
-        if self.current.is_some() {
-            self.metrics.set_elapsed(self.backend.position())
+    /// Jump back to A once playback crosses B. Only crossing counts, so
+    /// seeking past B by hand escapes the loop rather than snapping back.
+    fn check_loop(&mut self) {
+        let Some((a, b)) = self.ab_loop else {
+            return;
+        };
+
+        if self.current.is_none() || self.lost_output.is_some() {
+            return;
+        }
+
+        let pos = self.backend.position();
+
+        if self.last_position < b && pos >= b {
+            self.seek_to(a);
+            self.metrics.set_elapsed(a);
+        }
+    }
+
+    /// The flag re-arms whenever playback is outside the window, which covers
+    /// both new tracks and seeking back
+    fn check_ending_soon(&mut self) {
+        let Some(duration) = self.current.as_ref().map(|c| c.duration()) else {
+            return;
+        };
+
+        if duration.is_zero() || self.lost_output.is_some() {
+            return;
+        }
+
+        let remaining = duration.saturating_sub(self.backend.position());
+        if remaining > ENDING_SOON {
+            self.ending_soon_sent = false;
+        } else if !std::mem::replace(&mut self.ending_soon_sent, true) {
+            self.emit(PlayerEvent::TrackEndingSoon);
         }
-        self.tap_samples();
     }
 
-    fn tap_samples(&mut self) {
-        let samples = self.backend.drain_samples();
+    /// Reopen the output when its device goes away, e.g. when a USB DAC is
+    /// unplugged, and pick the current track back up where it stopped. Any
+    /// other stream error is only reported.
+    fn check_output(&mut self) {
+        match self.backend.take_stream_error() {
+            Some(OutputError::DeviceLost(err)) if self.lost_output.is_none() => {
+                self.lost_output = Some(LostOutput {
+                    elapsed: self.metrics.get_elapsed(),
+                    paused: self.backend.is_paused(),
+                    retry_at: Instant::now(),
+                    reported: false,
+                });
+                self.emit(PlayerEvent::OutputLost(err));
+            }
+            Some(OutputError::Other(err)) => self.emit(PlayerEvent::Error(err)),
+            _ => (),
+        }
+
+        let Some(lost) = self.lost_output.as_ref() else {
+            return;
+        };
+
+        if Instant::now() < lost.retry_at {
+            return;
+        }
 
-        if let Ok(mut tap) = self.metrics.audio_tap.try_lock() {
-            for sample in samples {
-                tap.push_back(sample);
-                if tap.len() > OSCILLO_BUFFER_CAPACITY {
-                    tap.pop_front();
+        // The chosen device may be gone for good, so settle for the default
+        let reopened = self
+            .backend
+            .set_output_device(self.output_device.as_deref())
+            .or_else(|_| self.backend.set_output_device(None));
+
+        match reopened {
+            Ok(_) => {
+                if let Some(lost) = self.lost_output.take() {
+                    self.resume_current(lost.elapsed, lost.paused);
                 }
             }
+            Err(e) => {
+                let Some(lost) = self.lost_output.as_mut() else {
+                    return;
+                };
+                lost.retry_at = Instant::now() + OUTPUT_RETRY;
+
+                if !std::mem::replace(&mut lost.reported, true) {
+                    self.emit(PlayerEvent::Error(format!(
+                        "Audio output was lost, retrying in the background\n{e}"
+                    )));
+                }
+            }
+        }
+    }
+
+    fn update_metrics(&mut self) {
+        // Hold on to the last known position while the output is down
+        if self.current.is_some() && self.lost_output.is_none() {
+            self.last_position = self.backend.position();
+            self.metrics.set_elapsed(self.last_position)
         }
     }
 
-    fn play_song(&mut self, song: ConcertusTrack) {
-        if let Err(e) = self.backend.play(&song.path()) {
+    /// Tracks starting part way through are cued first, so the output never
+    /// hears their beginning
+    fn play_song(&mut self, song: ConcertusTrack, start: Duration) {
+        let gain = self.replaygain.factor(song.replay_gain());
+        let loaded = match start.is_zero() {
+            true => self.backend.play(song.path(), gain),
+            false => self.backend.cue(song.path(), gain, start).map(|_| {
+                self.backend.resume();
+                self.metrics.set_elapsed(start);
+            }),
+        };
+
+        if let Err(e) = loaded {
             self.emit(PlayerEvent::Error(e.to_string()));
             return;
         }
 
-        self.current = Some(song.clone());
+        self.track_loaded(&song);
         self.metrics.set_playback_state(PlaybackState::Playing);
         self.emit(PlayerEvent::TrackStarted((song, false)));
     }
 
+    /// The track is loaded paused at `pos`, so none of it is heard before
+    /// playback is resumed
+    fn cue_song(&mut self, song: ConcertusTrack, pos: Duration) {
+        let gain = self.replaygain.factor(song.replay_gain());
+        if let Err(e) = self.backend.cue(song.path(), gain, pos) {
+            self.emit(PlayerEvent::Error(e.to_string()));
+            return;
+        }
+
+        self.track_loaded(&song);
+        self.metrics.set_playback_state(PlaybackState::Paused);
+        self.metrics.set_elapsed(pos);
+        self.emit(PlayerEvent::TrackCued(song));
+        self.emit(PlayerEvent::Paused);
+        self.emit(PlayerEvent::Seeked(pos));
+    }
+
+    fn track_loaded(&mut self, song: &ConcertusTrack) {
+        self.current = Some(song.clone());
+        self.ab_loop = None;
+        self.update_crossfade();
+    }
+
     fn set_next(&mut self, next: Option<ConcertusTrack>) {
         if self.backend.supports_gapless() {
-            if let Some(song) = &next {
-                if let Err(e) = self.backend.set_next(&song.path()) {
-                    self.emit(PlayerEvent::Error(e.to_string()));
-                    return;
+            match &next {
+                Some(song) => {
+                    let gain = self.replaygain.factor(song.replay_gain());
+                    if let Err(e) = self.backend.set_next(song.path(), gain) {
+                        self.emit(PlayerEvent::Error(e.to_string()));
+                        return;
+                    }
                 }
+                None => self.backend.clear_next(),
             }
 
             self.next = next;
+            self.update_crossfade();
         }
     }
 
+    /// Tracks from the same album are never crossfaded, so albums which are
+    /// meant to play gaplessly stay that way
+    fn update_crossfade(&mut self) {
+        let fade = match (&self.current, &self.next) {
+            (Some(current), Some(next)) if !current.same_album(next) => self.crossfade,
+            _ => Duration::ZERO,
+        };
+        self.backend.set_crossfade(fade);
+    }
+
     fn clear_next(&mut self) {
+        self.backend.clear_next();
         self.next = None
     }
 
@@ -140,11 +381,13 @@ impl PlayerCore {
             true => {
                 self.backend.resume();
                 self.metrics.set_playback_state(PlaybackState::Playing);
+                self.emit(PlayerEvent::Resumed);
             }
 
             false => {
                 self.backend.pause();
                 self.metrics.set_playback_state(PlaybackState::Paused);
+                self.emit(PlayerEvent::Paused);
             }
         }
     }
@@ -152,25 +395,137 @@ impl PlayerCore {
     fn stop(&mut self) {
         self.backend.stop();
         self.current = None;
+        self.ab_loop = None;
         self.metrics.set_playback_state(PlaybackState::Stopped);
         self.metrics.reset();
     }
 
     fn seek_forward(&mut self, secs: u64) {
-        if !self.backend.is_stopped() {
-            let _ = self.backend.seek_forward(secs);
+        if !self.backend.is_stopped() && self.backend.seek_forward(secs).is_ok() {
+            self.emit(PlayerEvent::Seeked(self.backend.position()));
         }
     }
 
     fn seek_back(&mut self, secs: u64) {
         if !self.backend.is_stopped() {
-            if let Err(e) = self.backend.seek_back(secs) {
-                self.emit(PlayerEvent::Error(e.to_string()));
+            match self.backend.seek_back(secs) {
+                Ok(_) => self.emit(PlayerEvent::Seeked(self.backend.position())),
+                Err(e) => self.emit(PlayerEvent::Error(e.to_string())),
+            }
+        }
+    }
+
+    fn seek_to(&mut self, pos: Duration) {
+        if !self.backend.is_stopped() {
+            match self.backend.seek_to(pos) {
+                Ok(_) => self.emit(PlayerEvent::Seeked(pos)),
+                Err(e) => self.emit(PlayerEvent::Error(e.to_string())),
+            }
+        }
+    }
+
+    fn set_volume(&mut self, volume: u8) {
+        self.metrics.set_volume(volume.min(MAX_VOLUME));
+        self.apply_volume();
+        self.emit_volume();
+    }
+
+    fn adjust_volume(&mut self, delta: i8) {
+        let volume = self.metrics.get_volume().saturating_add_signed(delta);
+        self.metrics.set_muted(false);
+        self.set_volume(volume);
+    }
+
+    fn toggle_mute(&mut self) {
+        self.metrics.set_muted(!self.metrics.is_muted());
+        self.apply_volume();
+        self.emit_volume();
+    }
+
+    fn emit_volume(&self) {
+        self.emit(PlayerEvent::VolumeChanged {
+            volume: self.metrics.get_volume(),
+            muted: self.metrics.is_muted(),
+        });
+    }
+
+    fn set_fade(&mut self, fade: f32) {
+        self.fade = fade.clamp(0.0, 1.0);
+        self.apply_volume();
+    }
+
+    fn apply_volume(&mut self) {
+        // Loudness is perceived logarithmically, a cubic curve keeps each
+        // volume step sounding roughly even. Fades ride the same curve.
+        let gain = match self.metrics.is_muted() {
+            true => 0.0,
+            false => {
+                let level = self.metrics.get_volume() as f32 / MAX_VOLUME as f32;
+                (level * self.fade).powi(3)
             }
+        };
+        self.backend.set_volume(gain);
+    }
+
+    fn set_speed(&mut self, speed: f32) {
+        let speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+        match self.backend.set_speed(speed) {
+            Ok(_) => self.metrics.set_speed(speed),
+            Err(e) => self.emit(PlayerEvent::Error(e.to_string())),
+        }
+    }
+
+    fn set_equalizer(&mut self, settings: EqSettings) {
+        self.backend.set_equalizer(&settings);
+    }
+
+    fn set_output_device(&mut self, name: Option<String>) {
+        let (elapsed, paused) = match self.lost_output.take() {
+            Some(lost) => (lost.elapsed, lost.paused),
+            None => (self.metrics.get_elapsed(), self.backend.is_paused()),
+        };
+
+        self.output_device = name;
+        if let Err(e) = self
+            .backend
+            .set_output_device(self.output_device.as_deref())
+        {
+            self.emit(PlayerEvent::Error(format!(
+                "Could not open output device\n{e}"
+            )));
+        }
+        self.resume_current(elapsed, paused);
+    }
+
+    /// Restart the current track at `elapsed` after the output stream was
+    /// replaced, without announcing it as a new track
+    fn resume_current(&mut self, elapsed: Duration, paused: bool) {
+        let Some(song) = self.current.clone() else {
+            return;
+        };
+
+        let gain = self.replaygain.factor(song.replay_gain());
+        let restarted = match paused {
+            true => self.backend.cue(song.path(), gain, elapsed),
+            false => self
+                .backend
+                .play(song.path(), gain)
+                .and_then(|_| self.backend.seek_to(elapsed)),
+        };
+
+        if let Err(e) = restarted {
+            self.emit(PlayerEvent::Error(e.to_string()));
+            self.stop();
+            self.emit(PlayerEvent::PlaybackStopped);
+            return;
+        }
+
+        if let Some(next) = self.next.take() {
+            self.set_next(Some(next));
         }
     }
 
     fn emit(&self, event: PlayerEvent) {
-        let _ = self.events.send(event);
+        self.events.publish(event);
     }
 }
@@ -0,0 +1,333 @@
+use anyhow::{Result, anyhow};
+use std::{
+    fs::File,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+use symphonia::{
+    core::{
+        audio::SampleBuffer,
+        codecs::{CODEC_TYPE_NULL, Decoder, DecoderOptions},
+        errors::Error as SymphoniaError,
+        formats::{FormatOptions, FormatReader, SeekMode, SeekTo},
+        io::MediaSourceStream,
+        meta::MetadataOptions,
+        probe::Hint,
+        units::TimeBase,
+    },
+    default::{get_codecs, get_probe},
+};
+
+use super::OutputSpec;
+
+static NEXT_SERIAL: AtomicU64 = AtomicU64::new(0);
+
+/// Enough to open a track again without touching the decoder in use, which
+/// the render thread may be reading from
+#[derive(Clone)]
+pub(super) struct TrackSource {
+    path: PathBuf,
+    gain: f32,
+    // Shared by every decoder opened from this source
+    serial: u64,
+}
+
+impl TrackSource {
+    /// A decoder which can stand in for the one this source was taken from
+    pub fn open(&self, spec: OutputSpec) -> Result<TrackDecoder> {
+        let mut decoder = TrackDecoder::open(&self.path, spec, self.gain)?;
+        decoder.source.serial = self.serial;
+        Ok(decoder)
+    }
+
+    pub fn serial(&self) -> u64 {
+        self.serial
+    }
+}
+
+/// Decodes a single file into interleaved f32 frames which match the
+/// channel count and sample rate of the output stream.
+pub(super) struct TrackDecoder {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    time_base: Option<TimeBase>,
+    source_rate: u32,
+    duration: Option<Duration>,
+
+    spec: OutputSpec,
+    sample_buf: Option<SampleBuffer<f32>>,
+
+    // Frames already converted to the output channel count, but still at the
+    // source sample rate. `cursor` is a fractional index into these frames,
+    // advanced by `step` for every frame written to the output.
+    frames: Vec<f32>,
+    cursor: f64,
+    step: f64,
+
+    // Frames to discard after an accurate seek lands before its target
+    skip: u64,
+    exhausted: bool,
+
+    // Linear ReplayGain adjustment for this track
+    gain: f32,
+    source: TrackSource,
+}
+
+impl TrackDecoder {
+    pub fn open(path: &Path, spec: OutputSpec, gain: f32) -> Result<Self> {
+        let file = File::open(path)?;
+        let mss = MediaSourceStream::new(Box::new(file), Default::default());
+
+        let mut hint = Hint::new();
+        if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+            hint.with_extension(ext);
+        }
+
+        // Trims encoder delay & padding (mp3/aac) so that back to back tracks
+        // meet exactly where the artist intended
+        let format_opts = FormatOptions {
+            enable_gapless: true,
+            ..Default::default()
+        };
+
+        let format = get_probe()
+            .format(&hint, mss, &format_opts, &MetadataOptions::default())?
+            .format;
+
+        let track = format
+            .tracks()
+            .iter()
+            .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+            .ok_or_else(|| anyhow!("No playable audio track in {}", path.display()))?;
+
+        let params = track.codec_params.clone();
+        let track_id = track.id;
+
+        let source_rate = params
+            .sample_rate
+            .ok_or_else(|| anyhow!("Unknown sample rate for {}", path.display()))?;
+
+        let duration = params
+            .n_frames
+            .map(|n| Duration::from_secs_f64(n as f64 / source_rate as f64));
+
+        let decoder = get_codecs().make(&params, &DecoderOptions::default())?;
+
+        Ok(TrackDecoder {
+            format,
+            decoder,
+            track_id,
+            time_base: params.time_base,
+            source_rate,
+            duration,
+
+            spec,
+            sample_buf: None,
+
+            frames: Vec::with_capacity(8192),
+            cursor: 0.0,
+            step: source_rate as f64 / spec.rate as f64,
+
+            skip: 0,
+            exhausted: false,
+
+            gain,
+            source: TrackSource {
+                path: path.to_path_buf(),
+                gain,
+                serial: NEXT_SERIAL.fetch_add(1, Ordering::Relaxed),
+            },
+        })
+    }
+
+    pub fn source(&self) -> &TrackSource {
+        &self.source
+    }
+
+    pub fn serial(&self) -> u64 {
+        self.source.serial
+    }
+
+    /// Decode the first packet ahead of time so that the track can begin
+    /// the instant it is handed to the render thread.
+    pub fn prime(&mut self) {
+        if !self.exhausted && self.frames.is_empty() && !self.fill() {
+            self.exhausted = true;
+        }
+    }
+
+    /// Write the next output frame into `out`, with the track's gain
+    /// applied. Returns false once the track has been fully played.
+    pub fn next_frame(&mut self, out: &mut [f32]) -> bool {
+        if !self.interpolate(out) {
+            return false;
+        }
+
+        out.iter_mut().for_each(|s| *s *= self.gain);
+        true
+    }
+
+    fn interpolate(&mut self, out: &mut [f32]) -> bool {
+        let ch = self.spec.channels;
+
+        loop {
+            let idx = self.cursor as usize;
+            let available = self.frames.len() / ch;
+
+            if idx + 1 < available {
+                let frac = (self.cursor - idx as f64) as f32;
+                let a = &self.frames[idx * ch..(idx + 1) * ch];
+                let b = &self.frames[(idx + 1) * ch..(idx + 2) * ch];
+
+                for (o, (a, b)) in out.iter_mut().zip(a.iter().zip(b)) {
+                    *o = a + (b - a) * frac;
+                }
+                self.cursor += self.step;
+                return true;
+            }
+
+            if self.exhausted {
+                // Nothing left to interpolate against, flush the final frame
+                if idx < available {
+                    out.copy_from_slice(&self.frames[idx * ch..(idx + 1) * ch]);
+                    self.cursor += self.step;
+                    return true;
+                }
+                return false;
+            }
+
+            // Retain the last frame so interpolation carries across packets
+            let consumed = idx.min(available.saturating_sub(1));
+            self.frames.drain(..consumed * ch);
+            self.cursor -= consumed as f64;
+
+            if !self.fill() {
+                self.exhausted = true;
+            }
+        }
+    }
+
+    /// Length of the track in output frames, when the container reports it
+    pub fn total_frames(&self) -> Option<u64> {
+        self.duration
+            .map(|dur| (dur.as_secs_f64() * self.spec.rate as f64) as u64)
+    }
+
+    /// Seek to `pos`, returning the position playback will resume from.
+    pub fn seek(&mut self, pos: Duration) -> Result<Duration> {
+        if let Some(dur) = self.duration
+            && pos >= dur
+        {
+            self.frames.clear();
+            self.exhausted = true;
+            return Ok(dur);
+        }
+
+        let seeked = self.format.seek(
+            SeekMode::Accurate,
+            SeekTo::Time {
+                time: pos.into(),
+                track_id: Some(self.track_id),
+            },
+        )?;
+
+        self.decoder.reset();
+        self.frames.clear();
+        self.cursor = 0.0;
+        self.exhausted = false;
+        self.skip = self.ts_to_frames(seeked.required_ts.saturating_sub(seeked.actual_ts));
+
+        Ok(pos)
+    }
+
+    fn ts_to_frames(&self, ts: u64) -> u64 {
+        match self.time_base {
+            Some(tb) => ts * tb.numer as u64 * self.source_rate as u64 / tb.denom as u64,
+            None => ts,
+        }
+    }
+
+    fn fill(&mut self) -> bool {
+        self.decode_packet().unwrap_or(false)
+    }
+
+    /// Decode packets until at least one frame has been appended. Returns
+    /// Ok(false) at the end of the stream.
+    fn decode_packet(&mut self) -> Result<bool> {
+        loop {
+            let packet = match self.format.next_packet() {
+                Ok(packet) => packet,
+                Err(SymphoniaError::IoError(e)) if e.kind() == ErrorKind::UnexpectedEof => {
+                    return Ok(false);
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+
+            let decoded = match self.decoder.decode(&packet) {
+                Ok(decoded) => decoded,
+                // Corrupt packets are skipped rather than ending the track
+                Err(SymphoniaError::DecodeError(_)) => continue,
+                Err(e) => return Err(e.into()),
+            };
+
+            let spec = *decoded.spec();
+            let src_ch = spec.channels.count();
+            let n_frames = decoded.frames();
+
+            if n_frames == 0 {
+                continue;
+            }
+
+            let needed = decoded.capacity() * src_ch;
+            if self
+                .sample_buf
+                .as_ref()
+                .is_some_and(|b| b.capacity() < needed)
+            {
+                self.sample_buf = None;
+            }
+
+            let buf = self
+                .sample_buf
+                .get_or_insert_with(|| SampleBuffer::new(decoded.capacity() as u64, spec));
+            buf.copy_interleaved_ref(decoded);
+
+            let mut samples = buf.samples();
+
+            if self.skip > 0 {
+                let skipped = (self.skip as usize).min(n_frames);
+                self.skip -= skipped as u64;
+                samples = &samples[skipped * src_ch..];
+
+                if samples.is_empty() {
+                    continue;
+                }
+            }
+
+            map_channels(samples, src_ch, &mut self.frames, self.spec.channels);
+            return Ok(true);
+        }
+    }
+}
+
+fn map_channels(src: &[f32], src_ch: usize, dst: &mut Vec<f32>, dst_ch: usize) {
+    if src_ch == dst_ch {
+        dst.extend_from_slice(src);
+        return;
+    }
+
+    for frame in src.chunks_exact(src_ch) {
+        match (src_ch, dst_ch) {
+            (1, _) => dst.extend(std::iter::repeat_n(frame[0], dst_ch)),
+            (_, 1) => dst.push(frame.iter().sum::<f32>() / src_ch as f32),
+            _ => dst.extend((0..dst_ch).map(|c| frame.get(c).copied().unwrap_or(0.0))),
+        }
+    }
+}
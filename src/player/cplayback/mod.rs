@@ -0,0 +1,314 @@
+//! In-tree playback engine built directly on symphonia & cpal.
+//!
+//! Audio is rendered on a thread of its own, a short way ahead of the output,
+//! and handed to the cpal callback through a lock-free ring. The callback
+//! only copies frames out, applying the volume and holding back while
+//! paused, so a slow read from disk can never cost a dropped buffer.
+//!
+//! The track queued with `Player::queue` is opened and primed ahead of time,
+//! so when the current track runs dry the render thread swaps to it within
+//! the same block - no silence is ever inserted between the two. With a
+//! crossfade set, the queued track is instead mixed in over the final
+//! seconds of the current one.
+//!
+//! Tracks are opened, seeked and primed on the player thread, then sent to
+//! the render thread. Anything already rendered is flushed, so a seek is
+//! heard straight away rather than once the ring has played out.
+//!
+//! Playback speed is changed after decoding by a WSOLA time-stretch, so the
+//! pitch is preserved and the position is always counted in track time. The
+//! equalizer runs last, so the sample tap sees exactly what is heard.
+
+mod decoder;
+mod output;
+mod render;
+mod ring;
+mod stretch;
+
+use crate::player::{AudioTap, backend::OutputError};
+use anyhow::Result;
+use crossbeam_channel::Sender;
+use std::{
+    path::Path,
+    sync::{
+        Arc, Mutex, MutexGuard, PoisonError,
+        atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use crate::player::equalizer::{EqControl, EqSettings};
+use decoder::{TrackDecoder, TrackSource};
+use output::Output;
+use render::Command;
+use ring::Block;
+
+#[derive(Clone, Copy)]
+struct OutputSpec {
+    rate: u32,
+    channels: usize,
+}
+
+struct Shared {
+    // Published by the output callback as blocks are played
+    frames_played: AtomicU64,
+    // Load generation of the last track to finish, or to run dry with
+    // nothing queued after it
+    ended: AtomicU64,
+    drained: AtomicU64,
+
+    paused: AtomicBool,
+    // f32 bits
+    volume: AtomicU32,
+    speed: AtomicU32,
+    eq: EqControl,
+    // The track the render thread is playing, for seeks to reopen
+    current: Mutex<Option<TrackSource>>,
+    // Last error reported by the output stream, a lost device outranking
+    // anything else
+    stream_error: Mutex<Option<OutputError>>,
+    // Tells the player thread to look at `ended` or `stream_error`
+    waker: Sender<()>,
+}
+
+impl Shared {
+    fn current(&self) -> MutexGuard<'_, Option<TrackSource>> {
+        self.current.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn volume(&self) -> f32 {
+        f32::from_bits(self.volume.load(Ordering::Relaxed))
+    }
+
+    fn speed(&self) -> f32 {
+        f32::from_bits(self.speed.load(Ordering::Relaxed))
+    }
+
+    fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Called from the output callback once `block` has been played, or
+    /// skipped over by a flush
+    fn reached(&self, block: &Block, played: bool) {
+        if played {
+            self.frames_played.store(block.position, Ordering::Relaxed);
+        }
+        if block.drained {
+            self.drained.store(block.load, Ordering::Relaxed);
+        }
+        if block.ended {
+            self.ended.store(block.load, Ordering::SeqCst);
+            self.wake();
+        }
+    }
+
+    fn report_error(&self, err: cpal::StreamError) {
+        let mut slot = self
+            .stream_error
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        OutputError::record(&mut slot, err);
+        self.wake();
+    }
+
+    /// Never blocks, a wake up already pending covers this one too
+    fn wake(&self) {
+        let _ = self.waker.try_send(());
+    }
+}
+
+pub struct Player {
+    tap: Arc<AudioTap>,
+    shared: Arc<Shared>,
+    output: Output,
+    // Bumped for every track loaded, so that stale ends are told apart
+    load: u64,
+    loaded: bool,
+}
+
+impl Player {
+    /// `waker` is poked from the audio thread whenever a track finishes or
+    /// the output stream fails. Everything played is copied into `tap`.
+    pub fn new(waker: Sender<()>, tap: Arc<AudioTap>) -> Result<Self> {
+        let shared = Arc::new(Shared {
+            frames_played: AtomicU64::new(0),
+            ended: AtomicU64::new(0),
+            drained: AtomicU64::new(0),
+            paused: AtomicBool::new(false),
+            volume: AtomicU32::new(1.0f32.to_bits()),
+            speed: AtomicU32::new(1.0f32.to_bits()),
+            eq: EqControl::default(),
+            current: Mutex::new(None),
+            stream_error: Mutex::new(None),
+            waker,
+        });
+
+        let output = output::open(None, Arc::clone(&shared), Arc::clone(&tap))?;
+
+        Ok(Player {
+            tap,
+            shared,
+            output,
+            load: 0,
+            loaded: false,
+        })
+    }
+
+    /// Move playback to another output device, or the default with `None`.
+    /// Decoders are built for the old device's sample rate, so the current
+    /// and queued tracks are dropped and need to be played again.
+    pub fn set_output_device(&mut self, name: Option<&str>) -> Result<()> {
+        self.stop();
+        self.clear_queue();
+
+        self.output = output::open(name, Arc::clone(&self.shared), Arc::clone(&self.tap))?;
+
+        // Anything reported by the old stream no longer applies
+        self.take_stream_error();
+
+        Ok(())
+    }
+
+    /// Replace the current track. The queued track, if any, is preserved.
+    /// `gain` scales this track alone, on top of the player volume.
+    pub fn play(&mut self, path: &Path, gain: f32) -> Result<()> {
+        let mut decoder = TrackDecoder::open(path, self.output.spec, gain)?;
+        decoder.prime();
+
+        self.replace_current(Some(decoder), Duration::ZERO, false);
+        Ok(())
+    }
+
+    /// Replace the current track with one paused at `pos`, so that nothing
+    /// before it is ever heard
+    pub fn cue(&mut self, path: &Path, gain: f32, pos: Duration) -> Result<()> {
+        let mut decoder = TrackDecoder::open(path, self.output.spec, gain)?;
+        let landed = decoder.seek(pos)?;
+        decoder.prime();
+
+        self.replace_current(Some(decoder), landed, true);
+        Ok(())
+    }
+
+    fn replace_current(&mut self, decoder: Option<TrackDecoder>, pos: Duration, paused: bool) {
+        self.load += 1;
+        self.loaded = decoder.is_some();
+        *self.shared.current() = decoder.as_ref().map(|d| d.source().clone());
+
+        let position = self.frames(pos);
+        self.shared.paused.store(paused, Ordering::Relaxed);
+        self.shared.frames_played.store(position, Ordering::Relaxed);
+
+        self.output.send(Command::Load {
+            decoder,
+            load: self.load,
+            position,
+        });
+    }
+
+    /// Open and pre-decode the track which should follow the current one.
+    pub fn queue(&mut self, path: &Path, gain: f32) -> Result<()> {
+        let mut decoder = TrackDecoder::open(path, self.output.spec, gain)?;
+        decoder.prime();
+
+        self.output.send(Command::Queue(Some(decoder)));
+        Ok(())
+    }
+
+    pub fn clear_queue(&mut self) {
+        self.output.send(Command::Queue(None));
+    }
+
+    /// Fade into the queued track over `dur`, zero for a gapless transition
+    pub fn set_crossfade(&mut self, dur: Duration) {
+        self.output.send(Command::Crossfade(self.frames(dur)));
+    }
+
+    pub fn pause(&mut self) {
+        self.shared.paused.store(true, Ordering::Relaxed);
+    }
+
+    pub fn resume(&mut self) {
+        self.shared.paused.store(false, Ordering::Relaxed);
+    }
+
+    pub fn stop(&mut self) {
+        self.replace_current(None, Duration::ZERO, false);
+    }
+
+    /// The current track keeps playing while a second decoder seeks, which
+    /// then takes its place
+    pub fn seek(&mut self, pos: Duration) -> Result<()> {
+        let Some(source) = self.shared.current().clone() else {
+            return Ok(());
+        };
+
+        let mut decoder = source.open(self.output.spec)?;
+        let landed = decoder.seek(pos)?;
+        decoder.prime();
+
+        // The track ran out in the meantime, its successor stays put
+        if self
+            .shared
+            .current()
+            .as_ref()
+            .is_none_or(|current| current.serial() != source.serial())
+        {
+            return Ok(());
+        }
+
+        let position = self.frames(landed);
+        self.shared.frames_played.store(position, Ordering::Relaxed);
+        self.output.send(Command::Seek { decoder, position });
+
+        Ok(())
+    }
+
+    pub fn set_volume(&mut self, gain: f32) {
+        self.shared.volume.store(gain.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Playback rate, 1.0 bypasses the time-stretch entirely
+    pub fn set_speed(&mut self, speed: f32) {
+        self.shared.speed.store(speed.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn set_equalizer(&mut self, settings: &EqSettings) {
+        self.shared.eq.store(settings);
+    }
+
+    pub fn position(&self) -> Duration {
+        let frames = self.shared.frames_played.load(Ordering::Relaxed);
+        Duration::from_secs_f64(frames as f64 / self.output.spec.rate as f64)
+    }
+
+    fn frames(&self, dur: Duration) -> u64 {
+        (dur.as_secs_f64() * self.output.spec.rate as f64) as u64
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.shared.is_paused()
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        !self.loaded || self.shared.drained.load(Ordering::Relaxed) == self.load
+    }
+
+    /// Returns an error raised by the output stream since the last call. Once
+    /// the device is reported lost the stream should be considered dead.
+    pub fn take_stream_error(&self) -> Option<OutputError> {
+        self.shared
+            .stream_error
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+    }
+
+    /// Returns true once per track which finishes playing, regardless of
+    /// whether a queued track took its place. Tracks replaced before the
+    /// output got to their end don't count.
+    pub fn track_ended(&self) -> bool {
+        self.loaded && self.shared.ended.swap(0, Ordering::SeqCst) == self.load
+    }
+}
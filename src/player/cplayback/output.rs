@@ -0,0 +1,171 @@
+use anyhow::{Result, bail};
+use cpal::{
+    Device, FromSample, OutputCallbackInfo, Sample, SampleFormat, SizedSample, Stream,
+    StreamConfig,
+    traits::{DeviceTrait, StreamTrait},
+};
+use crossbeam_channel::Sender;
+use std::{
+    sync::Arc,
+    thread::{self, JoinHandle},
+};
+
+use super::{
+    OutputSpec, Shared,
+    render::{Command, Renderer},
+    ring::{self, Consumer},
+};
+use crate::player::{AudioTap, output_device::find_output_device};
+
+/// A running output stream, along with the thread rendering audio for it.
+/// Dropping it stops both.
+pub(super) struct Output {
+    pub spec: OutputSpec,
+    commands: Sender<Command>,
+    render: Option<JoinHandle<()>>,
+    _stream: Stream,
+}
+
+impl Output {
+    pub fn send(&self, command: Command) {
+        let _ = self.commands.send(command);
+    }
+}
+
+impl Drop for Output {
+    fn drop(&mut self) {
+        self.send(Command::Shutdown);
+        if let Some(render) = self.render.take() {
+            let _ = render.join();
+        }
+    }
+}
+
+/// Open the output device called `name` (or the default) using its preferred
+/// configuration, and start rendering for it.
+pub(super) fn open(name: Option<&str>, shared: Arc<Shared>, tap: Arc<AudioTap>) -> Result<Output> {
+    let device = find_output_device(name)?;
+
+    let supported = device.default_output_config()?;
+    let config = supported.config();
+    let spec = OutputSpec {
+        rate: config.sample_rate.0,
+        channels: config.channels as usize,
+    };
+
+    let (producer, consumer) = ring::ring(Renderer::blocks(spec), spec.channels);
+    let feed = Feed {
+        ring: consumer,
+        channels: spec.channels,
+        offset: 0,
+    };
+
+    let stream = match supported.sample_format() {
+        SampleFormat::F32 => build::<f32>(&device, &config, Arc::clone(&shared), tap, feed),
+        SampleFormat::F64 => build::<f64>(&device, &config, Arc::clone(&shared), tap, feed),
+        SampleFormat::I16 => build::<i16>(&device, &config, Arc::clone(&shared), tap, feed),
+        SampleFormat::I32 => build::<i32>(&device, &config, Arc::clone(&shared), tap, feed),
+        SampleFormat::U16 => build::<u16>(&device, &config, Arc::clone(&shared), tap, feed),
+        SampleFormat::U8 => build::<u8>(&device, &config, Arc::clone(&shared), tap, feed),
+        format => bail!("Unsupported output sample format: {format}"),
+    }?;
+
+    let (commands, receiver) = crossbeam_channel::unbounded();
+    let renderer = Renderer::new(shared, producer, spec);
+    let render = thread::spawn(move || renderer.run(receiver));
+
+    stream.play()?;
+
+    Ok(Output {
+        spec,
+        commands,
+        render: Some(render),
+        _stream: stream,
+    })
+}
+
+fn build<T>(
+    device: &Device,
+    config: &StreamConfig,
+    shared: Arc<Shared>,
+    tap: Arc<AudioTap>,
+    mut feed: Feed,
+) -> Result<Stream>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let errors = Arc::clone(&shared);
+    let (channels, rate) = (config.channels as usize, config.sample_rate.0);
+
+    let stream = device.build_output_stream(
+        config,
+        move |data: &mut [T], _: &OutputCallbackInfo| {
+            tap.set_format(channels, rate);
+            render(&shared, &tap, &mut feed, data)
+        },
+        move |err| errors.report_error(err),
+        None,
+    )?;
+
+    Ok(stream)
+}
+
+/// Runs on the output callback, so it never blocks, allocates or decodes
+fn render<T>(shared: &Shared, tap: &AudioTap, feed: &mut Feed, data: &mut [T])
+where
+    T: Sample + FromSample<f32>,
+{
+    let volume = shared.volume();
+    let paused = shared.is_paused();
+    let flush = feed.ring.flush();
+
+    for out in data.chunks_exact_mut(feed.channels) {
+        match feed.next_frame(shared, flush, paused) {
+            Some(frame) => {
+                for (o, s) in out.iter_mut().zip(frame) {
+                    *o = T::from_sample(*s * volume);
+                }
+                tap.push(frame);
+            }
+            None => out.fill(T::EQUILIBRIUM),
+        }
+    }
+}
+
+/// The output callback's end of the ring
+struct Feed {
+    ring: Consumer,
+    channels: usize,
+    // Frames of the front block already played
+    offset: usize,
+}
+
+impl Feed {
+    /// The next frame to play, `None` while paused or should rendering fall
+    /// behind. Blocks from before the latest flush are skipped, though any
+    /// track they saw end still counts as ended.
+    fn next_frame(&mut self, shared: &Shared, flush: u64, paused: bool) -> Option<&[f32]> {
+        loop {
+            let block = self.ring.front()?;
+            let stale = block.flush < flush;
+
+            if !stale {
+                if paused {
+                    return None;
+                }
+                if self.offset < block.frames {
+                    let start = self.offset * self.channels;
+                    self.offset += 1;
+                    return self
+                        .ring
+                        .front()
+                        .map(|block| &block.samples[start..start + self.channels]);
+                }
+            }
+
+            shared.reached(block, !stale);
+            self.ring.pop();
+            self.offset = 0;
+        }
+    }
+}
@@ -0,0 +1,301 @@
+//! The render thread decodes, mixes, time-stretches and equalizes audio a
+//! little ahead of the output, leaving the callback nothing to do but copy.
+//!
+//! It owns the timeline outright. The player reaches it through `Command`s,
+//! and through the atomics in `Shared` for anything which is read per block.
+
+use crossbeam_channel::{Receiver, RecvTimeoutError, TryRecvError};
+use std::{f32::consts::FRAC_PI_2, sync::Arc, time::Duration};
+
+use super::{
+    OutputSpec, Shared,
+    decoder::TrackDecoder,
+    ring::{BLOCK_FRAMES, Producer},
+    stretch::Stretcher,
+};
+use crate::player::equalizer::Equalizer;
+
+/// How far ahead of the output audio is rendered. Leaves room for output
+/// callbacks asking for as much as ~90ms at a time.
+pub(super) const BUFFER_SECS: f64 = 0.2;
+
+pub(super) enum Command {
+    /// Replace the current track, `None` stops playback. Everything rendered
+    /// from here on is tagged with `load`.
+    Load {
+        decoder: Option<TrackDecoder>,
+        load: u64,
+        position: u64,
+    },
+    /// Takes over from the current decoder, if that is still the same track
+    Seek {
+        decoder: TrackDecoder,
+        position: u64,
+    },
+    Queue(Option<TrackDecoder>),
+    /// Crossfade length in output frames, zero disables it
+    Crossfade(u64),
+    Shutdown,
+}
+
+#[derive(Default)]
+struct Timeline {
+    current: Option<TrackDecoder>,
+    next: Option<TrackDecoder>,
+    // Frames of `current` played so far
+    played: u64,
+    // Set when `current` runs out, until the block it ran out in is done
+    ended: bool,
+
+    // Length of the fade into `next` in output frames, zero disables it
+    fade_frames: u64,
+    // Frames of `next` which have already been mixed in by a fade
+    next_frames: u64,
+    scratch: Vec<f32>,
+}
+
+impl Timeline {
+    /// Pull a frame from the current track, rolling straight into the queued
+    /// track the moment the current one is exhausted.
+    fn pull(&mut self, frame: &mut [f32]) -> bool {
+        loop {
+            let Some(current) = self.current.as_mut() else {
+                return false;
+            };
+
+            if current.next_frame(frame) {
+                self.mix_next(frame);
+                self.played += 1;
+                return true;
+            }
+
+            // A crossfade has already played the start of the next track
+            self.current = self.next.take();
+            self.played = std::mem::take(&mut self.next_frames);
+            self.ended = true;
+        }
+    }
+
+    fn mix_next(&mut self, frame: &mut [f32]) {
+        if self.fade_frames == 0 {
+            return;
+        }
+
+        let (Some(current), Some(next)) = (self.current.as_ref(), self.next.as_mut()) else {
+            return;
+        };
+
+        let Some(total) = current.total_frames() else {
+            return;
+        };
+
+        let remaining = total.saturating_sub(self.played);
+        if remaining >= self.fade_frames {
+            return;
+        }
+
+        self.scratch.resize(frame.len(), 0.0);
+        if !next.next_frame(&mut self.scratch) {
+            return;
+        }
+        self.next_frames += 1;
+
+        // Equal power curves keep the perceived loudness steady mid-fade
+        let t = 1.0 - remaining as f32 / self.fade_frames as f32;
+        let (fade_out, fade_in) = ((t * FRAC_PI_2).cos(), (t * FRAC_PI_2).sin());
+
+        for (out, incoming) in frame.iter_mut().zip(&self.scratch) {
+            *out = *out * fade_out + incoming * fade_in;
+        }
+    }
+}
+
+pub(super) struct Renderer {
+    shared: Arc<Shared>,
+    ring: Producer,
+    spec: OutputSpec,
+
+    timeline: Timeline,
+    stretch: Stretcher,
+    eq: Equalizer,
+    frame: Vec<f32>,
+
+    flush: u64,
+    load: u64,
+    // How long to wait for the output to make room in a full ring
+    poll: Duration,
+}
+
+impl Renderer {
+    pub fn new(shared: Arc<Shared>, ring: Producer, spec: OutputSpec) -> Self {
+        Renderer {
+            shared,
+            ring,
+            spec,
+
+            timeline: Timeline::default(),
+            stretch: Stretcher::new(spec.rate, spec.channels),
+            eq: Equalizer::new(spec.rate, spec.channels),
+            frame: vec![0.0; spec.channels],
+
+            flush: 0,
+            load: 0,
+            poll: Duration::from_secs_f64(BUFFER_SECS / 10.0),
+        }
+    }
+
+    /// Blocks needed to hold `BUFFER_SECS` of audio
+    pub fn blocks(spec: OutputSpec) -> usize {
+        (spec.rate as f64 * BUFFER_SECS / BLOCK_FRAMES as f64).ceil() as usize
+    }
+
+    /// Keep the ring topped up until told to shut down. Commands are handled
+    /// between blocks, so none waits on more than one.
+    pub fn run(mut self, commands: Receiver<Command>) {
+        loop {
+            let command = match self.render() {
+                true => match commands.try_recv() {
+                    Ok(command) => command,
+                    Err(TryRecvError::Empty) => continue,
+                    Err(TryRecvError::Disconnected) => return,
+                },
+                // Nothing will be rendered before the next command
+                false if self.timeline.current.is_none() => match commands.recv() {
+                    Ok(command) => command,
+                    Err(_) => return,
+                },
+                false => match commands.recv_timeout(self.poll) {
+                    Ok(command) => command,
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                },
+            };
+
+            if !self.apply(command) {
+                return;
+            }
+        }
+    }
+
+    /// Returns false on shutdown
+    fn apply(&mut self, command: Command) -> bool {
+        match command {
+            Command::Load {
+                decoder,
+                load,
+                position,
+            } => {
+                self.timeline.current = decoder;
+                self.timeline.played = position;
+                self.load = load;
+                self.publish_current();
+                self.flush();
+            }
+            Command::Seek { decoder, position } => {
+                // The track ran out in the meantime, its successor stays put
+                if self
+                    .timeline
+                    .current
+                    .as_ref()
+                    .is_none_or(|current| current.serial() != decoder.serial())
+                {
+                    return true;
+                }
+                self.timeline.current = Some(decoder);
+                self.timeline.played = position;
+                self.flush();
+            }
+            Command::Queue(next) => {
+                self.timeline.next = next;
+                self.timeline.next_frames = 0;
+            }
+            Command::Crossfade(frames) => self.timeline.fade_frames = frames,
+            Command::Shutdown => return false,
+        }
+        true
+    }
+
+    /// Render the next block into the ring. Returns false when there is
+    /// nothing to render, or no room for it.
+    fn render(&mut self) -> bool {
+        if self.timeline.current.is_none() {
+            return false;
+        }
+        let Some(block) = self.ring.vacant() else {
+            return false;
+        };
+
+        self.eq.sync(&self.shared.eq);
+        self.stretch.set_speed(self.shared.speed());
+
+        let timeline = &mut self.timeline;
+        block.frames = 0;
+        for out in block.samples.chunks_exact_mut(self.frame.len()) {
+            let more = match self.stretch.is_active() {
+                true => self
+                    .stretch
+                    .next_frame(&mut self.frame, |f| timeline.pull(f)),
+                false => timeline.pull(&mut self.frame),
+            };
+            if !more {
+                break;
+            }
+
+            self.eq.process_frame(&mut self.frame);
+            out.copy_from_slice(&self.frame);
+            block.frames += 1;
+        }
+
+        block.flush = self.flush;
+        block.load = self.load;
+        block.position = timeline.played;
+        block.ended = std::mem::take(&mut timeline.ended);
+        block.drained = timeline.current.is_none();
+
+        let ended = block.ended;
+        self.ring.commit();
+
+        if ended {
+            self.publish_current();
+        }
+        true
+    }
+
+    /// Drop everything waiting in the ring, so the output picks up from the
+    /// timeline as it is now
+    fn flush(&mut self) {
+        self.flush = self.ring.flush();
+        self.stretch.reset();
+        self.rewind_next();
+    }
+
+    /// Undo a fade which was cut short, so the queued track starts over. A
+    /// fresh decoder replaces the one the fade has already read from.
+    fn rewind_next(&mut self) {
+        if std::mem::take(&mut self.timeline.next_frames) == 0 {
+            return;
+        }
+
+        // Should the file have gone, the fade simply starts from where the
+        // last one left off
+        let rewound = self
+            .timeline
+            .next
+            .as_ref()
+            .and_then(|next| next.source().open(self.spec).ok());
+
+        if let Some(mut decoder) = rewound {
+            decoder.prime();
+            self.timeline.next = Some(decoder);
+        }
+    }
+
+    /// Let the player know which track it would be seeking in
+    fn publish_current(&self) {
+        *self.shared.current() = self
+            .timeline
+            .current
+            .as_ref()
+            .map(|current| current.source().clone());
+    }
+}
@@ -0,0 +1,200 @@
+//! Lock-free queue of rendered audio, from the render thread to the output
+//! callback.
+//!
+//! Audio travels in fixed size blocks, each carrying what the player should
+//! learn once it has been heard: the position reached, and whether a track
+//! ran out along the way. There is exactly one `Producer` and one `Consumer`,
+//! and each slot is only ever touched by one of them at a time.
+
+use std::{
+    cell::UnsafeCell,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+};
+
+/// Frames per block, which is also how finely the position is reported
+pub(super) const BLOCK_FRAMES: usize = 256;
+
+#[derive(Default)]
+pub(super) struct Block {
+    /// Interleaved, only the first `frames` frames are valid
+    pub samples: Vec<f32>,
+    pub frames: usize,
+    /// Flush generation this was rendered in, blocks from before a flush are
+    /// skipped rather than played
+    pub flush: u64,
+    /// Load generation of the track rendered into this block
+    pub load: u64,
+    /// Position in the current track once this block has played, in frames
+    pub position: u64,
+    /// A track ran out part way through this block
+    pub ended: bool,
+    /// Nothing was left to render after this block
+    pub drained: bool,
+}
+
+struct Ring {
+    slots: Box<[UnsafeCell<Block>]>,
+    // Running totals, slots are indexed modulo their count
+    written: AtomicUsize,
+    read: AtomicUsize,
+    flush: AtomicU64,
+}
+
+// Slots between `read` and `written` belong to the consumer, all others to
+// the producer, so no slot is ever accessed from both sides at once
+unsafe impl Sync for Ring {}
+
+pub(super) fn ring(blocks: usize, channels: usize) -> (Producer, Consumer) {
+    let slots = (0..blocks.max(2))
+        .map(|_| {
+            UnsafeCell::new(Block {
+                samples: vec![0.0; BLOCK_FRAMES * channels],
+                ..Default::default()
+            })
+        })
+        .collect();
+
+    let ring = Arc::new(Ring {
+        slots,
+        written: AtomicUsize::new(0),
+        read: AtomicUsize::new(0),
+        flush: AtomicU64::new(0),
+    });
+
+    (
+        Producer {
+            ring: Arc::clone(&ring),
+        },
+        Consumer { ring },
+    )
+}
+
+pub(super) struct Producer {
+    ring: Arc<Ring>,
+}
+
+impl Producer {
+    /// The next block to render into, `None` while the ring is full
+    pub fn vacant(&mut self) -> Option<&mut Block> {
+        let written = self.ring.written.load(Ordering::Relaxed);
+        let read = self.ring.read.load(Ordering::Acquire);
+        let len = self.ring.slots.len();
+
+        if written - read == len {
+            return None;
+        }
+
+        // SAFETY: the slot is past `written`, so the consumer won't read it
+        // until `commit` hands it over
+        Some(unsafe { &mut *self.ring.slots[written % len].get() })
+    }
+
+    /// Hand the block last returned by `vacant` over to the consumer
+    pub fn commit(&mut self) {
+        self.ring.written.fetch_add(1, Ordering::Release);
+    }
+
+    /// Mark every block written so far as stale. Returns the generation to
+    /// tag blocks with from now on.
+    pub fn flush(&mut self) -> u64 {
+        self.ring.flush.fetch_add(1, Ordering::Release) + 1
+    }
+}
+
+pub(super) struct Consumer {
+    ring: Arc<Ring>,
+}
+
+impl Consumer {
+    /// The oldest block not yet popped
+    pub fn front(&self) -> Option<&Block> {
+        let read = self.ring.read.load(Ordering::Relaxed);
+        if read == self.ring.written.load(Ordering::Acquire) {
+            return None;
+        }
+
+        // SAFETY: the slot was committed, and the producer won't reuse it
+        // until `pop` gives it back
+        Some(unsafe { &*self.ring.slots[read % self.ring.slots.len()].get() })
+    }
+
+    pub fn pop(&mut self) {
+        self.ring.read.fetch_add(1, Ordering::Release);
+    }
+
+    /// Blocks tagged with an older generation than this are stale
+    pub fn flush(&self) -> u64 {
+        self.ring.flush.load(Ordering::Acquire)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn blocks_arrive_in_order() {
+        const TOTAL: u64 = 10_000;
+        let (mut producer, mut consumer) = ring(4, 2);
+
+        let writer = thread::spawn(move || {
+            let mut sent = 0;
+            while sent < TOTAL {
+                let Some(block) = producer.vacant() else {
+                    thread::yield_now();
+                    continue;
+                };
+                block.position = sent;
+                block.samples.fill(sent as f32);
+                block.frames = BLOCK_FRAMES;
+                producer.commit();
+                sent += 1;
+            }
+        });
+
+        let mut received = 0;
+        while received < TOTAL {
+            let Some(block) = consumer.front() else {
+                thread::yield_now();
+                continue;
+            };
+            assert_eq!(block.position, received);
+            assert!(block.samples.iter().all(|s| *s == received as f32));
+            consumer.pop();
+            received += 1;
+        }
+
+        writer.join().unwrap();
+    }
+
+    #[test]
+    fn full_until_popped() {
+        let (mut producer, mut consumer) = ring(2, 1);
+
+        for _ in 0..2 {
+            assert!(producer.vacant().is_some());
+            producer.commit();
+        }
+        assert!(producer.vacant().is_none());
+
+        consumer.pop();
+        assert!(producer.vacant().is_some());
+    }
+
+    #[test]
+    fn flush_outdates_written_blocks() {
+        let (mut producer, consumer) = ring(2, 1);
+
+        let stale = consumer.flush();
+        producer.vacant().unwrap().flush = stale;
+        producer.commit();
+
+        let fresh = producer.flush();
+        assert!(consumer.front().unwrap().flush < consumer.flush());
+        assert_eq!(fresh, consumer.flush());
+    }
+}
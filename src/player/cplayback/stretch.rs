@@ -0,0 +1,260 @@
+//! Pitch preserving time-stretch (WSOLA).
+//!
+//! Windows of the source are overlap-added at a fixed hop, while the read
+//! position advances by `hop * speed`. Each window is nudged within a small
+//! tolerance to wherever it best lines up with the audio already written,
+//! which keeps the waveform continuous without shifting its pitch.
+
+use std::f32::consts::TAU;
+
+const WINDOW_SECS: f64 = 0.04;
+const TOLERANCE_SECS: f64 = 0.01;
+
+// Comparing every 4th sample is plenty to find the best alignment
+const CORRELATION_STRIDE: usize = 4;
+
+pub(super) struct Stretcher {
+    channels: usize,
+    window: usize,
+    hop: usize,
+    tolerance: usize,
+    hann: Vec<f32>,
+    speed: f32,
+
+    // Interleaved source frames which a future window may still read
+    input: Vec<f32>,
+    frame: Vec<f32>,
+    // Ideal start of the next window, as a frame index into `input`
+    read_pos: f64,
+    // Frame which follows on from the previous window, `None` after a reset
+    natural: Option<usize>,
+
+    // Second half of the previous window, waiting to be overlapped
+    tail: Vec<f32>,
+    ready: Vec<f32>,
+    ready_pos: usize,
+}
+
+impl Stretcher {
+    pub fn new(rate: u32, channels: usize) -> Self {
+        let window = ((rate as f64 * WINDOW_SECS) as usize).max(2) & !1;
+        let hop = window / 2;
+
+        // Periodic Hann, so windows at 50% overlap sum to exactly one
+        let hann = (0..window)
+            .map(|i| 0.5 - 0.5 * (TAU * i as f32 / window as f32).cos())
+            .collect();
+
+        Stretcher {
+            channels,
+            window,
+            hop,
+            tolerance: (rate as f64 * TOLERANCE_SECS) as usize,
+            hann,
+            speed: 1.0,
+
+            input: Vec::with_capacity(window * channels * 4),
+            frame: vec![0.0; channels],
+            read_pos: 0.0,
+            natural: None,
+
+            tail: vec![0.0; hop * channels],
+            ready: Vec::with_capacity(hop * channels),
+            ready_pos: 0,
+        }
+    }
+
+    /// At normal speed the stretcher is bypassed entirely
+    pub fn is_active(&self) -> bool {
+        self.speed != 1.0
+    }
+
+    pub fn set_speed(&mut self, speed: f32) {
+        if speed != self.speed {
+            self.speed = speed;
+            if !self.is_active() {
+                self.reset();
+            }
+        }
+    }
+
+    /// Discard buffered audio, e.g. after a seek
+    pub fn reset(&mut self) {
+        self.input.clear();
+        self.read_pos = 0.0;
+        self.natural = None;
+        self.tail.fill(0.0);
+        self.ready.clear();
+        self.ready_pos = 0;
+    }
+
+    /// Write the next stretched frame into `out`, reading the source through
+    /// `pull`. Returns false once the source runs dry.
+    pub fn next_frame(
+        &mut self,
+        out: &mut [f32],
+        mut pull: impl FnMut(&mut [f32]) -> bool,
+    ) -> bool {
+        if self.ready_pos >= self.ready.len() && !self.synthesize(&mut pull) {
+            return false;
+        }
+
+        let ch = self.channels;
+        out.copy_from_slice(&self.ready[self.ready_pos..self.ready_pos + ch]);
+        self.ready_pos += ch;
+        true
+    }
+
+    /// Overlap-add one more window, producing `hop` frames of output
+    fn synthesize(&mut self, pull: &mut impl FnMut(&mut [f32]) -> bool) -> bool {
+        let ch = self.channels;
+        let target = self.read_pos as usize;
+
+        // Enough input to slide the window across the whole search range
+        while self.input.len() < (target + self.tolerance + self.window) * ch {
+            if !pull(&mut self.frame) {
+                return false;
+            }
+            self.input.extend_from_slice(&self.frame);
+        }
+
+        let start = match self.natural {
+            Some(natural) => self.best_offset(target, natural),
+            None => target,
+        };
+
+        self.ready.clear();
+        self.ready_pos = 0;
+
+        for i in 0..self.hop {
+            let rising = self.hann[i];
+            let falling = self.hann[self.hop + i];
+
+            for c in 0..ch {
+                let head = self.input[(start + i) * ch + c] * rising;
+                self.ready.push(self.tail[i * ch + c] + head);
+                self.tail[i * ch + c] = self.input[(start + self.hop + i) * ch + c] * falling;
+            }
+        }
+
+        self.natural = Some(start + self.hop);
+        self.read_pos += self.hop as f64 * self.speed as f64;
+
+        // Drop input which no future window can reach. Above ~1.5x the read
+        // position runs past the previous window, so only the natural
+        // continuation bounds what must be kept.
+        let reachable = (self.read_pos as usize).saturating_sub(self.tolerance);
+        let consumed = reachable.min(start + self.hop);
+        if consumed > 0 {
+            self.input.drain(..consumed * ch);
+            self.read_pos -= consumed as f64;
+            self.natural = Some(start + self.hop - consumed);
+        }
+
+        true
+    }
+
+    /// Find the window start near `target` which best continues the audio
+    /// that follows the previous window at `natural`
+    fn best_offset(&self, target: usize, natural: usize) -> usize {
+        let ch = self.channels;
+        let lo = target.saturating_sub(self.tolerance);
+        let hi = target + self.tolerance;
+
+        let mut best = (target, f32::MIN);
+        for candidate in lo..=hi {
+            let score = (0..self.hop)
+                .step_by(CORRELATION_STRIDE)
+                .flat_map(|i| (0..ch).map(move |c| i * ch + c))
+                .map(|idx| self.input[candidate * ch + idx] * self.input[natural * ch + idx])
+                .sum::<f32>();
+
+            if score > best.1 {
+                best = (candidate, score);
+            }
+        }
+
+        best.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::{MAX_SPEED, MIN_SPEED};
+
+    const RATE: u32 = 44_100;
+    const CHANNELS: usize = 2;
+
+    // Stretch a second of a stereo tone, returning the output frame count
+    fn stretch(speed: f32) -> usize {
+        let total = RATE as usize;
+        let mut stretcher = Stretcher::new(RATE, CHANNELS);
+        stretcher.set_speed(speed);
+
+        let mut pos = 0;
+        let mut pull = |frame: &mut [f32]| {
+            if pos == total {
+                return false;
+            }
+            let sample = (TAU * 440.0 * pos as f32 / RATE as f32).sin();
+            frame.fill(sample);
+            pos += 1;
+            true
+        };
+
+        let mut out = [0.0; CHANNELS];
+        let mut frames = 0;
+        while stretcher.next_frame(&mut out, &mut pull) {
+            assert!(out.iter().all(|s| s.is_finite() && s.abs() <= 1.01));
+            frames += 1;
+        }
+        frames
+    }
+
+    fn assert_duration(speed: f32) {
+        let expected = RATE as f32 / speed;
+        let frames = stretch(speed) as f32;
+
+        // Windows still waiting on input at the end are dropped
+        let slack = RATE as f32 * (WINDOW_SECS + TOLERANCE_SECS) as f32 * 2.0;
+        assert!(
+            (frames - expected).abs() <= slack,
+            "{speed}x: {frames} frames, expected about {expected}"
+        );
+    }
+
+    #[test]
+    fn stretches_at_double_speed() {
+        assert_duration(2.0);
+    }
+
+    #[test]
+    fn stretches_at_max_speed() {
+        assert_duration(MAX_SPEED);
+    }
+
+    #[test]
+    fn stretches_at_min_speed() {
+        assert_duration(MIN_SPEED);
+    }
+
+    #[test]
+    fn reset_after_fast_playback() {
+        let mut stretcher = Stretcher::new(RATE, CHANNELS);
+        stretcher.set_speed(MAX_SPEED);
+
+        let mut out = [0.0; CHANNELS];
+        let mut ones = |frame: &mut [f32]| {
+            frame.fill(1.0);
+            true
+        };
+        for _ in 0..RATE {
+            assert!(stretcher.next_frame(&mut out, &mut ones));
+        }
+
+        stretcher.reset();
+        assert!(stretcher.next_frame(&mut out, &mut ones));
+        assert_eq!(out, [0.0; CHANNELS], "first frame fades in from silence");
+    }
+}
@@ -0,0 +1,184 @@
+//! Ten band graphic equalizer, built from one peaking biquad per band.
+//!
+//! Settings cross over to the audio thread through `EqControl`. Every change
+//! bumps its version, so filters are only redesigned when something moved.
+
+use std::{
+    f32::consts::{SQRT_2, TAU},
+    sync::atomic::{AtomicU32, AtomicU64, Ordering},
+};
+
+pub const EQ_FREQUENCIES: [f32; 10] = [
+    31.0, 62.0, 125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0, 16000.0,
+];
+pub const EQ_BANDS: usize = EQ_FREQUENCIES.len();
+/// Band gains are limited to +/- this many dB
+pub const EQ_MAX_GAIN: f32 = 12.0;
+
+// Roughly one octave per band, so neighbouring bands meet near -3dB
+const BAND_Q: f32 = SQRT_2;
+
+/// Gains in dB. `preamp` applies to the whole signal, ahead of the bands.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct EqSettings {
+    pub preamp: f32,
+    pub gains: [f32; EQ_BANDS],
+}
+
+impl EqSettings {
+    pub fn is_flat(&self) -> bool {
+        self.preamp == 0.0 && self.gains.iter().all(|g| *g == 0.0)
+    }
+}
+
+#[derive(Default)]
+pub struct EqControl {
+    // f32 bits
+    preamp: AtomicU32,
+    gains: [AtomicU32; EQ_BANDS],
+    version: AtomicU64,
+}
+
+impl EqControl {
+    pub fn store(&self, settings: &EqSettings) {
+        self.preamp
+            .store(settings.preamp.to_bits(), Ordering::Relaxed);
+        for (gain, db) in self.gains.iter().zip(settings.gains) {
+            gain.store(db.to_bits(), Ordering::Relaxed);
+        }
+        self.version.fetch_add(1, Ordering::Release);
+    }
+
+    fn load(&self) -> EqSettings {
+        EqSettings {
+            preamp: f32::from_bits(self.preamp.load(Ordering::Relaxed)),
+            gains: std::array::from_fn(|i| f32::from_bits(self.gains[i].load(Ordering::Relaxed))),
+        }
+    }
+}
+
+/// Normalized coefficients, with a0 divided out
+#[derive(Clone, Copy)]
+struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl Biquad {
+    /// Peaking filter from the RBJ audio EQ cookbook
+    fn peaking(freq: f32, rate: f32, db: f32) -> Self {
+        let a = 10f32.powf(db / 40.0);
+        let w0 = TAU * freq / rate;
+        let alpha = w0.sin() / (2.0 * BAND_Q);
+        let cos = w0.cos();
+        let a0 = 1.0 + alpha / a;
+
+        Biquad {
+            b0: (1.0 + alpha * a) / a0,
+            b1: (-2.0 * cos) / a0,
+            b2: (1.0 - alpha * a) / a0,
+            a1: (-2.0 * cos) / a0,
+            a2: (1.0 - alpha / a) / a0,
+        }
+    }
+
+    fn run(&self, s: &mut FilterState, x: f32) -> f32 {
+        let y = self.b0 * x + self.b1 * s.x1 + self.b2 * s.x2 - self.a1 * s.y1 - self.a2 * s.y2;
+        *s = FilterState {
+            x1: x,
+            x2: s.x1,
+            y1: y,
+            y2: s.y1,
+        };
+        y
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct FilterState {
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+/// Per-stream filter bank. Bands left at 0dB, or above the Nyquist limit of
+/// the stream, are skipped entirely.
+pub struct Equalizer {
+    rate: f32,
+    version: u64,
+    preamp: f32,
+    filters: [Option<Biquad>; EQ_BANDS],
+    // One set of filter histories per channel
+    state: Vec<[FilterState; EQ_BANDS]>,
+}
+
+impl Equalizer {
+    pub fn new(rate: u32, channels: usize) -> Self {
+        Equalizer {
+            rate: rate as f32,
+            version: 0,
+            preamp: 1.0,
+            filters: [None; EQ_BANDS],
+            state: vec![[FilterState::default(); EQ_BANDS]; channels],
+        }
+    }
+
+    /// Pick up any changes made through `control`. Cheap enough to call for
+    /// every buffer.
+    pub fn sync(&mut self, control: &EqControl) {
+        let version = control.version.load(Ordering::Acquire);
+        if version == self.version {
+            return;
+        }
+        self.version = version;
+
+        let settings = control.load();
+        self.preamp = 10f32.powf(settings.preamp / 20.0);
+
+        let nyquist = self.rate / 2.0;
+        for (i, filter) in self.filters.iter_mut().enumerate() {
+            let (freq, db) = (EQ_FREQUENCIES[i], settings.gains[i]);
+            *filter = match db != 0.0 && freq < nyquist * 0.9 {
+                true => Some(Biquad::peaking(freq, self.rate, db)),
+                false => None,
+            };
+        }
+    }
+
+    pub fn is_bypassed(&self) -> bool {
+        self.preamp == 1.0 && self.filters.iter().all(Option::is_none)
+    }
+
+    pub fn process(&mut self, channel: usize, sample: f32) -> f32 {
+        if self.is_bypassed() {
+            return sample;
+        }
+
+        let Some(state) = self.state.get_mut(channel) else {
+            return sample;
+        };
+
+        let mut x = sample * self.preamp;
+        for (filter, s) in self.filters.iter().zip(state.iter_mut()) {
+            if let Some(filter) = filter {
+                x = filter.run(s, x);
+            }
+        }
+        x
+    }
+
+    /// Filter one interleaved frame in place
+    pub fn process_frame(&mut self, frame: &mut [f32]) {
+        if self.is_bypassed() {
+            return;
+        }
+
+        for (ch, sample) in frame.iter_mut().enumerate() {
+            *sample = self.process(ch, *sample);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use crossbeam_channel::{Receiver, Sender, TrySendError};
+use std::sync::{Arc, Mutex};
+
+use crate::player::PlayerEvent;
+
+/// Events an external subscriber can fall behind by before it starts
+/// missing them
+const EVENT_BOUND: usize = 256;
+
+/// Fans player events out to any number of consumers, e.g. the UI, a
+/// scrobbler or an IPC server. External subscribers each have their own
+/// bounded channel, so one which stops reading only ever misses its own
+/// events and never holds up the player thread. The app's own subscription
+/// is unbounded, as it must never miss a track starting or stopping.
+#[derive(Clone, Default)]
+pub struct EventBus {
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
+}
+
+struct Subscriber {
+    tx: Sender<PlayerEvent>,
+    lossless: bool,
+}
+
+impl EventBus {
+    pub fn subscribe(&self) -> Receiver<PlayerEvent> {
+        self.add(crossbeam_channel::bounded(EVENT_BOUND), false)
+    }
+
+    /// Reserved for the app, which drains its events every tick
+    pub(super) fn subscribe_lossless(&self) -> Receiver<PlayerEvent> {
+        self.add(crossbeam_channel::unbounded(), true)
+    }
+
+    fn add(
+        &self,
+        (tx, rx): (Sender<PlayerEvent>, Receiver<PlayerEvent>),
+        lossless: bool,
+    ) -> Receiver<PlayerEvent> {
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.push(Subscriber { tx, lossless });
+        }
+        rx
+    }
+
+    /// Subscribers which have dropped their receiver are forgotten
+    pub fn publish(&self, event: PlayerEvent) {
+        self.send(event, true);
+    }
+
+    /// For events the app raised itself, which it has no need to hear back
+    pub fn publish_external(&self, event: PlayerEvent) {
+        self.send(event, false);
+    }
+
+    fn send(&self, event: PlayerEvent, to_app: bool) {
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.retain(|sub| match sub.lossless {
+                // Unbounded, so this only fails once the app has gone
+                true => !to_app || sub.tx.send(event.clone()).is_ok(),
+                false => !matches!(
+                    sub.tx.try_send(event.clone()),
+                    Err(TrySendError::Disconnected(_))
+                ),
+            });
+        }
+    }
+}
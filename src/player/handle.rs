@@ -2,45 +2,107 @@ use anyhow::Result;
 use crossbeam_channel::{Receiver, Sender};
 use std::{sync::Arc, time::Duration};
 
-use crate::player::{
-    ConcertusTrack, PlaybackState, PlayerCommand, PlayerEvent, backend_cplayback::ConcertusEngine,
-    backend_rodio::RodioBackend, core::PlayerCore, metrics::PlaybackMetrics,
+use crate::{
+    Config,
+    player::{
+        AudioTap, BackendKind, ConcertusBackend, ConcertusTrack, EqSettings, EventBus,
+        PlaybackState, PlayerCommand, PlayerEvent, backend_cplayback::ConcertusEngine,
+        backend_null::NullBackend, backend_rodio::RodioBackend, core::PlayerCore,
+        metrics::PlaybackMetrics,
+    },
 };
 
 pub struct PlayerHandle {
     commands: Sender<PlayerCommand>,
+    bus: EventBus,
     events: Receiver<PlayerEvent>,
     metrics: Arc<PlaybackMetrics>,
+    display_visible: bool,
 }
 
 impl PlayerHandle {
-    pub fn spawn() -> Self {
-        // let backend = ConcertusEngine::new().expect("Failed to initialize backend");
-        let backend = RodioBackend::new().expect("Failed to initialize backend");
+    pub fn spawn(config: &Config) -> Self {
+        let kind = config.backend;
         let (cmd_tx, cmd_rx) = crossbeam_channel::bounded(32);
-        let (event_tx, event_rx) = crossbeam_channel::bounded(32);
+        // One pending wake up says all there is to say
+        let (wake_tx, wake_rx) = crossbeam_channel::bounded(1);
+        let bus = EventBus::default();
+        let events = bus.subscribe_lossless();
         let metrics = PlaybackMetrics::new();
 
-        PlayerCore::spawn(Box::new(backend), cmd_rx, event_tx, Arc::clone(&metrics));
+        let backend = match init_backend(kind, wake_tx, metrics.audio_tap()) {
+            Ok(backend) => backend,
+            Err(e) => {
+                // Keep the app usable without audio, and let the UI say why
+                bus.publish(PlayerEvent::Error(format!(
+                    "Failed to start {kind} backend, audio is disabled\n{e}"
+                )));
+                Box::new(NullBackend::new())
+            }
+        };
+
+        PlayerCore::spawn(
+            backend,
+            config,
+            cmd_rx,
+            wake_rx,
+            bus.clone(),
+            Arc::clone(&metrics),
+        );
 
         Self {
             commands: cmd_tx,
-            events: event_rx,
+            bus,
+            events,
             metrics,
+            display_visible: true,
         }
     }
 
     pub fn metrics(&self) -> Arc<PlaybackMetrics> {
         Arc::clone(&self.metrics)
     }
+
+    /// A stream of every player event from here on, independent of the one
+    /// the app itself reads
+    pub fn subscribe(&self) -> Receiver<PlayerEvent> {
+        self.bus.subscribe()
+    }
+
+    /// The queue lives outside the player thread, so changes to it are
+    /// announced from the app side, to everyone but the app itself
+    pub fn queue_changed(&self, queue: Vec<u64>) {
+        self.bus.publish_external(PlayerEvent::QueueChanged(queue));
+    }
+}
+
+fn init_backend(
+    kind: BackendKind,
+    waker: Sender<()>,
+    tap: Arc<AudioTap>,
+) -> Result<Box<dyn ConcertusBackend>> {
+    Ok(match kind {
+        BackendKind::Native => Box::new(ConcertusEngine::new(waker, tap)?),
+        BackendKind::Rodio => Box::new(RodioBackend::new(waker, tap)?),
+        BackendKind::Null => Box::new(NullBackend::new()),
+        BackendKind::Auto => match ConcertusEngine::new(waker.clone(), Arc::clone(&tap)) {
+            Ok(engine) => Box::new(engine),
+            Err(_) => Box::new(RodioBackend::new(waker, tap)?),
+        },
+    })
 }
 
 // =====================
 //    COMMAND HANDLER
 // =====================
 impl PlayerHandle {
-    pub fn play(&self, song: ConcertusTrack) -> Result<()> {
-        self.commands.send(PlayerCommand::Play(song))?;
+    pub fn play(&self, song: ConcertusTrack, start: Duration) -> Result<()> {
+        self.commands.send(PlayerCommand::Play(song, start))?;
+        Ok(())
+    }
+
+    pub fn cue(&self, song: ConcertusTrack, pos: Duration) -> Result<()> {
+        self.commands.send(PlayerCommand::Cue(song, pos))?;
         Ok(())
     }
 
@@ -73,6 +135,61 @@ impl PlayerHandle {
         self.commands.send(PlayerCommand::SeekBack(dur))?;
         Ok(())
     }
+
+    pub fn seek_to(&self, pos: Duration) -> Result<()> {
+        self.commands.send(PlayerCommand::SeekTo(pos))?;
+        Ok(())
+    }
+
+    pub fn set_volume(&self, volume: u8) -> Result<()> {
+        self.commands.send(PlayerCommand::SetVolume(volume))?;
+        Ok(())
+    }
+
+    pub fn adjust_volume(&self, delta: i8) -> Result<()> {
+        self.commands.send(PlayerCommand::AdjustVolume(delta))?;
+        Ok(())
+    }
+
+    pub fn toggle_mute(&self) -> Result<()> {
+        self.commands.send(PlayerCommand::ToggleMute)?;
+        Ok(())
+    }
+
+    pub fn set_speed(&self, speed: f32) -> Result<()> {
+        self.commands.send(PlayerCommand::SetSpeed(speed))?;
+        Ok(())
+    }
+
+    pub fn set_equalizer(&self, settings: EqSettings) -> Result<()> {
+        self.commands.send(PlayerCommand::SetEqualizer(settings))?;
+        Ok(())
+    }
+
+    pub fn set_output_device(&self, name: Option<String>) -> Result<()> {
+        self.commands.send(PlayerCommand::SetOutputDevice(name))?;
+        Ok(())
+    }
+
+    pub fn set_fade(&self, fade: f32) -> Result<()> {
+        self.commands.send(PlayerCommand::SetFade(fade))?;
+        Ok(())
+    }
+
+    pub fn set_loop(&self, region: Option<(Duration, Duration)>) -> Result<()> {
+        self.commands.send(PlayerCommand::SetLoop(region))?;
+        Ok(())
+    }
+
+    /// Only sent on change, as the UI reports this every frame
+    pub fn set_display_visible(&mut self, visible: bool) -> Result<()> {
+        if self.display_visible != visible {
+            self.commands
+                .send(PlayerCommand::SetDisplayVisible(visible))?;
+            self.display_visible = visible;
+        }
+        Ok(())
+    }
 }
 
 // ===============
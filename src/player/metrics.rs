@@ -1,18 +1,23 @@
-use crate::player::PlaybackState;
+use crate::player::{AudioTap, PlaybackState, TAP_CAPACITY, TapWindow};
 
 use std::{
-    collections::VecDeque,
     sync::{
-        atomic::{AtomicU64, AtomicU8, Ordering},
-        Arc, Mutex,
+        atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering},
+        Arc,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 pub struct PlaybackMetrics {
     state: AtomicU8,
     elapsed_ms: AtomicU64,
-    pub audio_tap: Mutex<VecDeque<f32>>,
+    /// When `elapsed_ms` was last set, in microseconds since `epoch`
+    stamped_us: AtomicU64,
+    epoch: Instant,
+    volume: AtomicU8,
+    muted: AtomicBool,
+    speed: AtomicU32,
+    audio_tap: Arc<AudioTap>,
 }
 
 impl PlaybackMetrics {
@@ -20,7 +25,12 @@ impl PlaybackMetrics {
         Arc::new(PlaybackMetrics {
             state: AtomicU8::new(0),
             elapsed_ms: AtomicU64::new(0),
-            audio_tap: Mutex::new(VecDeque::with_capacity(2048)),
+            stamped_us: AtomicU64::new(0),
+            epoch: Instant::now(),
+            volume: AtomicU8::new(super::MAX_VOLUME),
+            muted: AtomicBool::new(false),
+            speed: AtomicU32::new(1.0f32.to_bits()),
+            audio_tap: Arc::new(AudioTap::new(TAP_CAPACITY)),
         })
     }
 
@@ -31,8 +41,31 @@ impl PlaybackMetrics {
             .unwrap_or(PlaybackState::Stopped)
     }
 
+    /// The core only refreshes the position while it's on screen, so
+    /// playback carries it forward from the last update in between
     pub fn get_elapsed(&self) -> Duration {
-        Duration::from_millis(self.elapsed_ms.load(Ordering::Relaxed))
+        let elapsed = Duration::from_millis(self.elapsed_ms.load(Ordering::Relaxed));
+
+        match self.get_state() {
+            PlaybackState::Playing => {
+                let stamped = Duration::from_micros(self.stamped_us.load(Ordering::Relaxed));
+                let since = self.epoch.elapsed().saturating_sub(stamped);
+                elapsed + since.mul_f32(self.get_speed())
+            }
+            _ => elapsed,
+        }
+    }
+
+    pub fn get_volume(&self) -> u8 {
+        self.volume.load(Ordering::Relaxed)
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted.load(Ordering::Relaxed)
+    }
+
+    pub fn get_speed(&self) -> f32 {
+        f32::from_bits(self.speed.load(Ordering::Relaxed))
     }
 
     pub fn is_paused(&self) -> bool {
@@ -44,24 +77,43 @@ impl PlaybackMetrics {
     }
 
     pub fn set_playback_state(&self, state: PlaybackState) {
+        // Settle the position first, so that it doesn't jump on resume
+        self.set_elapsed(self.get_elapsed());
         self.state.store(state as u8, Ordering::Relaxed);
     }
 
+    pub fn set_volume(&self, volume: u8) {
+        self.volume.store(volume, Ordering::Relaxed)
+    }
+
+    pub fn set_muted(&self, muted: bool) {
+        self.muted.store(muted, Ordering::Relaxed)
+    }
+
+    pub fn set_speed(&self, speed: f32) {
+        self.set_elapsed(self.get_elapsed());
+        self.speed.store(speed.to_bits(), Ordering::Relaxed)
+    }
+
     pub fn set_elapsed(&self, d: Duration) {
         self.elapsed_ms
-            .store(d.as_millis() as u64, Ordering::Relaxed)
+            .store(d.as_millis() as u64, Ordering::Relaxed);
+        self.stamped_us
+            .store(self.epoch.elapsed().as_micros() as u64, Ordering::Relaxed)
     }
 
     pub fn reset(&self) {
-        self.set_elapsed(Duration::ZERO);
         self.set_playback_state(PlaybackState::Stopped);
-        self.drain_samples();
+        self.set_elapsed(Duration::ZERO);
     }
 
-    pub fn drain_samples(&self) -> Vec<f32> {
-        match self.audio_tap.try_lock() {
-            Ok(buf) => buf.iter().copied().collect(),
-            Err(_) => Vec::new(),
-        }
+    /// Handed to the backend, which writes everything it plays into it
+    pub fn audio_tap(&self) -> Arc<AudioTap> {
+        Arc::clone(&self.audio_tap)
+    }
+
+    /// Up to `frames` of the most recently played frames
+    pub fn latest_frames(&self, frames: usize) -> TapWindow {
+        self.audio_tap.latest(frames)
     }
 }
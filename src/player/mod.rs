@@ -1,31 +1,126 @@
 mod backend;
+mod backend_cplayback;
+mod backend_null;
 mod backend_rodio;
 mod core;
+mod cplayback;
+mod equalizer;
+mod events;
 mod handle;
 mod metrics;
+mod output_device;
+mod replay_gain;
+mod tap;
 mod track;
 
 pub use crate::player::track::ConcertusTrack;
+use anyhow::bail;
 use backend::ConcertusBackend;
+pub use equalizer::{EQ_BANDS, EQ_FREQUENCIES, EQ_MAX_GAIN, EqSettings};
+pub use events::EventBus;
 pub use handle::PlayerHandle;
 pub use metrics::PlaybackMetrics;
+pub use output_device::output_devices;
+pub use replay_gain::{ReplayGainConfig, ReplayGainMode};
+use std::time::Duration;
+pub use tap::{AudioTap, TapWindow};
 
-pub(crate) const OSCILLO_BUFFER_CAPACITY: usize = 2048;
+/// Frames shown by the oscilloscope
+pub(crate) const OSCILLO_BUFFER_CAPACITY: usize = 1024;
+/// Samples held by the audio tap, several times what any visualizer reads
+pub(crate) const TAP_CAPACITY: usize = 1 << 15;
+pub const MAX_VOLUME: u8 = 100;
+pub const MIN_SPEED: f32 = 0.5;
+pub const MAX_SPEED: f32 = 3.0;
+/// How long before the end of a track `TrackEndingSoon` goes out
+pub const ENDING_SOON: Duration = Duration::from_secs(10);
 
+#[derive(Clone)]
 pub enum PlayerEvent {
     TrackStarted((ConcertusTrack, bool)),
+    /// Loaded paused without being heard, e.g. a restored session. The track
+    /// only starts once `Resumed` follows.
+    TrackCued(ConcertusTrack),
     PlaybackStopped,
+    Paused,
+    Resumed,
+    /// Position playback continues from
+    Seeked(Duration),
+    VolumeChanged {
+        volume: u8,
+        muted: bool,
+    },
+    /// Sent once per track, `ENDING_SOON` before it ends. Seeking back out
+    /// of that window sends it again.
+    TrackEndingSoon,
+    /// Song ids of the queue, in play order
+    QueueChanged(Vec<u64>),
+    /// The output stream died, the player reopens it by itself
+    OutputLost(String),
     Error(String),
 }
 
 pub enum PlayerCommand {
-    Play(ConcertusTrack),
+    /// Starts a track from the given position
+    Play(ConcertusTrack, Duration),
+    /// Loads a track paused at the given position, ready to resume
+    Cue(ConcertusTrack, Duration),
     SetNext(Option<ConcertusTrack>),
     ClearNext,
     TogglePlayback,
     Stop,
     SeekForward(u64),
     SeekBack(u64),
+    SeekTo(Duration),
+    SetVolume(u8),
+    AdjustVolume(i8),
+    ToggleMute,
+    SetSpeed(f32),
+    SetEqualizer(EqSettings),
+    SetOutputDevice(Option<String>),
+    /// Extra volume scale for fade outs, from 0.0 to 1.0
+    SetFade(f32),
+    /// Section to repeat as (A, B), `None` plays straight through
+    SetLoop(Option<(Duration, Duration)>),
+    /// Whether the position is on screen, and so worth polling for
+    SetDisplayVisible(bool),
+}
+
+/// Which `ConcertusBackend` the player thread is started with
+#[derive(Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendKind {
+    /// Native engine, falling back to rodio, then silence
+    #[default]
+    Auto,
+    Native,
+    Rodio,
+    Null,
+}
+
+impl std::str::FromStr for BackendKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "native" => Ok(Self::Native),
+            "rodio" => Ok(Self::Rodio),
+            "null" => Ok(Self::Null),
+            _ => bail!("Unknown backend `{s}` (expected auto, native, rodio or null)"),
+        }
+    }
+}
+
+impl std::fmt::Display for BackendKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackendKind::Auto => write!(f, "auto"),
+            BackendKind::Native => write!(f, "native"),
+            BackendKind::Rodio => write!(f, "rodio"),
+            BackendKind::Null => write!(f, "null"),
+        }
+    }
 }
 
 #[derive(PartialEq, Eq)]
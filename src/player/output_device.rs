@@ -0,0 +1,30 @@
+use anyhow::{Result, anyhow};
+use cpal::{
+    Device,
+    traits::{DeviceTrait, HostTrait},
+};
+
+/// Names of every output device on the default host, in the order the host
+/// reports them
+pub fn output_devices() -> Vec<String> {
+    cpal::default_host()
+        .output_devices()
+        .map(|devices| devices.filter_map(|d| d.name().ok()).collect())
+        .unwrap_or_default()
+}
+
+/// The output device called `name`, falling back to the system default when
+/// no name is given or the device has gone missing
+pub(super) fn find_output_device(name: Option<&str>) -> Result<Device> {
+    let host = cpal::default_host();
+
+    let named = name.and_then(|name| {
+        host.output_devices()
+            .ok()?
+            .find(|d| d.name().is_ok_and(|n| n == name))
+    });
+
+    named
+        .or_else(|| host.default_output_device())
+        .ok_or_else(|| anyhow!("No audio output device available"))
+}
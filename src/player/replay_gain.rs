@@ -0,0 +1,60 @@
+use serde::Deserialize;
+
+use crate::library::ReplayGain;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplayGainMode {
+    Off,
+    #[default]
+    Track,
+    /// Keeps the relative loudness within an album intact. Tracks without
+    /// album gain fall back to their track gain.
+    Album,
+}
+
+/// The `[replaygain]` section of `config.toml`
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct ReplayGainConfig {
+    pub mode: ReplayGainMode,
+    /// Added to every tagged gain, in dB
+    pub preamp: f32,
+    /// Lower the gain of tracks whose tagged peak would otherwise clip
+    pub prevent_clipping: bool,
+}
+
+impl Default for ReplayGainConfig {
+    fn default() -> Self {
+        ReplayGainConfig {
+            mode: ReplayGainMode::default(),
+            preamp: 0.0,
+            prevent_clipping: true,
+        }
+    }
+}
+
+impl ReplayGainConfig {
+    /// Linear amplitude to play a track at. Untagged tracks are left as-is.
+    pub fn factor(&self, rg: &ReplayGain) -> f32 {
+        let (gain, peak) = match self.mode {
+            ReplayGainMode::Off => return 1.0,
+            ReplayGainMode::Track => (rg.track_gain, rg.track_peak),
+            ReplayGainMode::Album => match rg.album_gain {
+                Some(gain) => (Some(gain), rg.album_peak),
+                None => (rg.track_gain, rg.track_peak),
+            },
+        };
+
+        let Some(gain) = gain else {
+            return 1.0;
+        };
+
+        let factor = 10f32.powf((gain + self.preamp) / 20.0);
+
+        match peak {
+            Some(peak) if self.prevent_clipping && peak > 0.0 => factor.min(1.0 / peak),
+            _ => factor,
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering, fence};
+
+/// Attempts at a window before settling for whatever was read
+const READ_ATTEMPTS: usize = 3;
+
+/// Ring of the most recent interleaved samples sent to the output, shared
+/// between the audio thread and the visualizers without any locking.
+///
+/// There's a single writer, the audio thread. It works like a seqlock:
+/// slots are claimed before they're overwritten, and readers copy out the
+/// newest samples then check that none of them were claimed meanwhile.
+pub struct AudioTap {
+    samples: Box<[AtomicU32]>,
+    mask: usize,
+    // Total samples written, slots are indexed by this modulo the capacity
+    written: AtomicUsize,
+    // Runs ahead of `written` while slots are being overwritten
+    claimed: AtomicUsize,
+    channels: AtomicUsize,
+    sample_rate: AtomicU32,
+}
+
+/// A consistent run of interleaved samples, oldest first
+pub struct TapWindow {
+    pub samples: Vec<f32>,
+    pub channels: usize,
+    pub sample_rate: u32,
+}
+
+impl TapWindow {
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels
+    }
+
+    /// Samples of a single channel, a mono window repeats its only one
+    pub fn channel(&self, idx: usize) -> impl Iterator<Item = f32> + '_ {
+        self.samples
+            .iter()
+            .skip(idx.min(self.channels - 1))
+            .step_by(self.channels)
+            .copied()
+    }
+
+    /// Averages every frame down to a single sample
+    pub fn mono(&self) -> Vec<f32> {
+        self.samples
+            .chunks_exact(self.channels)
+            .map(|frame| frame.iter().sum::<f32>() / self.channels as f32)
+            .collect()
+    }
+}
+
+impl AudioTap {
+    /// `capacity` is rounded up to a power of two
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.next_power_of_two();
+
+        AudioTap {
+            samples: (0..capacity).map(|_| AtomicU32::new(0)).collect(),
+            mask: capacity - 1,
+            written: AtomicUsize::new(0),
+            claimed: AtomicUsize::new(0),
+            channels: AtomicUsize::new(2),
+            sample_rate: AtomicU32::new(44100),
+        }
+    }
+
+    /// Writer side. Cheap enough to call for every buffer, as nothing
+    /// happens unless the layout actually changed. Pads the ring out to a
+    /// whole frame of the new layout, so that windows start on a frame.
+    pub fn set_format(&self, channels: usize, sample_rate: u32) {
+        let channels = channels.max(1);
+        if self.channels.load(Ordering::Relaxed) == channels
+            && self.sample_rate.load(Ordering::Relaxed) == sample_rate
+        {
+            return;
+        }
+
+        let written = self.written.load(Ordering::Relaxed);
+        let aligned = written.next_multiple_of(channels);
+        self.claim(aligned);
+        for idx in written..aligned {
+            self.samples[idx & self.mask].store(0, Ordering::Relaxed);
+        }
+
+        self.channels.store(channels, Ordering::Release);
+        self.sample_rate.store(sample_rate, Ordering::Release);
+        self.written.store(aligned, Ordering::Release);
+    }
+
+    /// Writer side. Should only ever be handed whole frames.
+    pub fn push(&self, samples: &[f32]) {
+        let written = self.written.load(Ordering::Relaxed);
+        self.claim(written + samples.len());
+        for (idx, sample) in samples.iter().enumerate() {
+            self.samples[(written + idx) & self.mask].store(sample.to_bits(), Ordering::Relaxed);
+        }
+        self.written
+            .store(written + samples.len(), Ordering::Release);
+    }
+
+    /// Writer side. The fence orders the claim before every slot written
+    /// after it, so a reader which sees any of those samples sees the claim.
+    fn claim(&self, end: usize) {
+        self.claimed.store(end, Ordering::Relaxed);
+        fence(Ordering::Release);
+    }
+
+    /// Copies out up to `frames` of the newest frames. Windows are capped at
+    /// half the ring, which leaves the writer room to carry on while the
+    /// copy is made.
+    pub fn latest(&self, frames: usize) -> TapWindow {
+        let mut samples = Vec::new();
+        let mut channels = 1;
+        let mut sample_rate = 0;
+
+        for _ in 0..READ_ATTEMPTS {
+            let end = self.written.load(Ordering::Acquire);
+            channels = self.channels.load(Ordering::Acquire);
+            sample_rate = self.sample_rate.load(Ordering::Acquire);
+
+            let len = (frames * channels).min(end).min(self.samples.len() / 2);
+            let start = end - len + len % channels;
+
+            samples.clear();
+            samples.extend(
+                (start..end).map(|idx| {
+                    f32::from_bits(self.samples[idx & self.mask].load(Ordering::Relaxed))
+                }),
+            );
+
+            // Anything claimed past `start + capacity` reused a slot we read,
+            // and a layout change midway leaves the window out of step
+            fence(Ordering::Acquire);
+            let overwritten = self.claimed.load(Ordering::Relaxed) > start + self.samples.len();
+            let aligned =
+                end.is_multiple_of(channels) && self.channels.load(Ordering::Relaxed) == channels;
+
+            if !overwritten && aligned {
+                break;
+            }
+        }
+
+        TapWindow {
+            samples,
+            channels,
+            sample_rate,
+        }
+    }
+}
@@ -1,7 +1,7 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
 use crate::{
-    library::{SimpleSong, SongDatabase},
+    library::{ReplayGain, SimpleSong, SongDatabase},
     playback::ValidatedSong,
 };
 
@@ -9,6 +9,9 @@ use crate::{
 pub struct ConcertusTrack {
     id: u64,
     path: PathBuf,
+    album_id: Option<i64>,
+    duration: Duration,
+    replay_gain: ReplayGain,
 }
 
 impl PartialEq for ConcertusTrack {
@@ -24,6 +27,9 @@ impl TryFrom<&SimpleSong> for ConcertusTrack {
         Ok(Self {
             id: song.id,
             path: PathBuf::from(song.get_path()?),
+            album_id: Some(song.album_id),
+            duration: song.duration,
+            replay_gain: song.get_replay_gain().unwrap_or_default(),
         })
     }
 }
@@ -33,13 +39,22 @@ impl From<&ValidatedSong> for ConcertusTrack {
         ConcertusTrack {
             id: song.id(),
             path: song.path(),
+            album_id: Some(song.meta.album_id),
+            duration: song.meta.duration,
+            replay_gain: song.replay_gain,
         }
     }
 }
 
 impl ConcertusTrack {
     pub fn new(id: u64, path: PathBuf) -> Self {
-        ConcertusTrack { id, path }
+        ConcertusTrack {
+            id,
+            path,
+            album_id: None,
+            duration: Duration::ZERO,
+            replay_gain: ReplayGain::default(),
+        }
     }
 
     pub fn id(&self) -> u64 {
@@ -49,4 +64,17 @@ impl ConcertusTrack {
     pub fn path(&self) -> &PathBuf {
         &self.path
     }
+
+    /// Zero when unknown
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub fn replay_gain(&self) -> &ReplayGain {
+        &self.replay_gain
+    }
+
+    pub fn same_album(&self, other: &ConcertusTrack) -> bool {
+        self.album_id.is_some() && self.album_id == other.album_id
+    }
 }
@@ -13,7 +13,14 @@ impl AppLayout {
     pub fn new(area: Rect, state: &mut UiState) -> Self {
         let prog_height = match state.display_progress() {
             true => match (state.get_progress_display(), area.height > 20) {
-                (ProgressDisplay::Waveform | ProgressDisplay::Oscilloscope, true) => 6,
+                (
+                    ProgressDisplay::Waveform
+                    | ProgressDisplay::Oscilloscope
+                    | ProgressDisplay::Spectrum
+                    | ProgressDisplay::Meter
+                    | ProgressDisplay::Vectorscope,
+                    true,
+                ) => 6,
                 _ => 3,
             },
             false => 0,
@@ -27,6 +34,7 @@ impl AppLayout {
         let buffer_line_height = match state.player_is_active()
             || !state.multi_select_empty()
             || state.get_library_refresh_progress().is_some()
+            || state.get_waveform_batch().is_some()
         {
             true => 1,
             false => 0,
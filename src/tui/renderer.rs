@@ -16,6 +16,7 @@ use ratatui::{
 pub fn render(f: &mut Frame, state: &mut UiState) {
     if matches!(state.get_mode(), Mode::Fullscreen) {
         let [progress, bufferline] = get_fullscreen_layout(f.area());
+        state.display_state.progress_visible = true;
 
         Progress.render(progress, f.buffer_mut(), state);
         BufferLine.render(bufferline, f.buffer_mut(), state);
@@ -24,6 +25,7 @@ pub fn render(f: &mut Frame, state: &mut UiState) {
     }
 
     let layout = AppLayout::new(f.area(), state);
+    state.display_state.progress_visible = layout.progress_bar.height > 0;
     render_bg(state, f);
 
     SearchBar.render(layout.search_bar, f.buffer_mut(), state);
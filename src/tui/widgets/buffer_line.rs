@@ -1,7 +1,9 @@
 use crate::{
+    DurationStyle, get_readable_duration,
     library::SongInfo,
+    playback::RepeatMode,
     truncate_at_last_space,
-    tui::widgets::{PAUSE_ICON, QUEUE_ICON, SELECTED},
+    tui::widgets::{MUTED_ICON, PAUSE_ICON, QUEUE_ICON, SELECTED, VOLUME_ICON},
     ui_state::{DisplayTheme, UiState},
 };
 use ratatui::{
@@ -54,7 +56,7 @@ impl StatefulWidget for BufferLine {
 
         let selection_count = state.get_multi_select_indices().len();
 
-        get_multi_selection(selection_count, &theme).render(left, buf);
+        left_display(state, selection_count, &theme).render(left, buf);
         playing_title(state, &theme, center.width as usize).render(center, buf);
         queue_display(state, &theme, right.width as usize).render(right, buf);
     }
@@ -126,12 +128,54 @@ fn playing_title(state: &UiState, theme: &DisplayTheme, width: usize) -> Option<
     }
 }
 
-fn get_multi_selection(size: usize, theme: &DisplayTheme) -> Option<Line<'static>> {
+fn left_display(state: &UiState, selection_count: usize, theme: &DisplayTheme) -> Line<'static> {
+    let volume = match state.is_muted() {
+        true => Span::from(format!(" {MUTED_ICON} muted ")).fg(theme.text_muted),
+        false => {
+            Span::from(format!(" {VOLUME_ICON} {:>3}% ", state.get_volume())).fg(theme.text_muted)
+        }
+    };
+
+    let mut line = Line::from(volume).left_aligned();
+    if !state.get_eq_settings().is_flat() {
+        line.push_span(Span::from(format!("eq {} ", state.get_eq_label())).fg(theme.text_muted));
+    }
+
+    let repeat = state.playback.repeat();
+    if repeat != RepeatMode::Off {
+        line.push_span(Span::from(format!("repeat {repeat} ")).fg(theme.text_muted));
+    }
+
+    if let Some(current) = state.get_current_chapter() {
+        let total = state.get_chapters().len();
+        line.push_span(Span::from(format!("ch {}/{total} ", current + 1)).fg(theme.text_muted));
+    }
+
+    if let Some(remaining) = state.get_sleep_remaining() {
+        let remaining = get_readable_duration(remaining, DurationStyle::Compact);
+        line.push_span(Span::from(format!("sleep {remaining} ")).fg(theme.text_muted));
+    }
+
+    if let Some(batch) = state.get_waveform_batch() {
+        let status = match batch.paused {
+            true => "paused ",
+            false => "",
+        };
+        let progress = format!("{}/{}", batch.current, batch.total);
+        line.push_span(Span::from(format!("waveforms {status}{progress} ")).fg(theme.text_muted));
+    }
+
+    if let Some(selection) = get_multi_selection(selection_count, theme) {
+        line.push_span(selection);
+    }
+
+    line
+}
+
+fn get_multi_selection(size: usize, theme: &DisplayTheme) -> Option<Span<'static>> {
     let output = match size {
         0 => return None,
-        x => format!("{x:>3} {} ", SELECTED)
-            .fg(theme.accent)
-            .into_left_aligned_line(),
+        x => format!("{x:>3} {} ", SELECTED).fg(theme.accent),
     };
 
     Some(output)
@@ -9,7 +9,10 @@ mod tracklist;
 
 pub use buffer_line::BufferLine;
 pub use popup::PopupManager;
-pub use popups::{ErrorMsg, PlaylistPopup, RootManager, ThemeManager};
+pub use popups::{
+    ChapterPopup, EqualizerPopup, ErrorMsg, PlaylistPopup, RootManager, SeekPopup, SleepPopup,
+    ThemeManager,
+};
 pub use progress::Progress;
 pub use search::SearchBar;
 pub use sidebar::SideBarHandler;
@@ -20,8 +23,12 @@ const PAUSE_ICON: &str = "󰏤";
 const SELECTOR: &str = "⮞  ";
 const QUEUE_ICON: &str = "󰐑";
 const MUSIC_NOTE: &str = "♫";
+const CHAPTER_MARKER: &str = "▾";
+const PARTIALLY_PLAYED: [&str; 3] = ["◔", "◑", "◕"];
 const QUEUED: &str = "";
 const SELECTED: &str = "󱕣";
+const VOLUME_ICON: &str = "󰕾";
+const MUTED_ICON: &str = "󰝟";
 const WAVEFORM_WIDGET_HEIGHT: f64 = 50.0;
 
 static POPUP_PADDING: ratatui::widgets::Padding = ratatui::widgets::Padding {
@@ -6,7 +6,10 @@ use ratatui::{
 use crate::{
     tui::{
         ErrorMsg,
-        widgets::{PlaylistPopup, RootManager, ThemeManager},
+        widgets::{
+            ChapterPopup, EqualizerPopup, PlaylistPopup, RootManager, SeekPopup, SleepPopup,
+            ThemeManager,
+        },
     },
     ui_state::{PopupType, UiState},
 };
@@ -26,6 +29,10 @@ impl StatefulWidget for PopupManager {
             PopupType::Settings(_) => centered_rect(40, 40, area),
             PopupType::ThemeManager => centered_rect(40, 40, area),
             PopupType::Error(_) => centered_rect(40, 35, area),
+            PopupType::SeekTo => centered_rect(30, 30, area),
+            PopupType::Equalizer => centered_rect(40, 50, area),
+            PopupType::SleepTimer => centered_rect(30, 45, area),
+            PopupType::Chapters => centered_rect(40, 50, area),
             _ => return,
         };
 
@@ -36,6 +43,10 @@ impl StatefulWidget for PopupManager {
 
             PopupType::ThemeManager => ThemeManager.render(popup_rect, buf, state),
             PopupType::Error(_) => ErrorMsg.render(popup_rect, buf, state),
+            PopupType::SeekTo => SeekPopup.render(popup_rect, buf, state),
+            PopupType::Equalizer => EqualizerPopup.render(popup_rect, buf, state),
+            PopupType::SleepTimer => SleepPopup.render(popup_rect, buf, state),
+            PopupType::Chapters => ChapterPopup.render(popup_rect, buf, state),
             _ => unreachable!(),
         }
     }
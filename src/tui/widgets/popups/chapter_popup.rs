@@ -0,0 +1,61 @@
+use ratatui::{
+    layout::Alignment,
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, List, StatefulWidget, Widget},
+};
+
+use crate::{
+    DurationStyle, get_readable_duration,
+    tui::widgets::{POPUP_PADDING, SELECTOR},
+    ui_state::UiState,
+};
+
+pub struct ChapterPopup;
+impl StatefulWidget for ChapterPopup {
+    type State = UiState;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let theme = state.theme_manager.get_display_theme(true);
+        let current = state.get_current_chapter();
+
+        let block = Block::bordered()
+            .border_type(theme.border_type)
+            .border_style(theme.border)
+            .title(" Chapters ")
+            .title_bottom(" [Enter] jump / [Esc] ")
+            .title_alignment(Alignment::Center)
+            .padding(POPUP_PADDING)
+            .bg(theme.bg);
+
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let chapters = state
+            .get_chapters()
+            .iter()
+            .enumerate()
+            .map(|(idx, chapter)| {
+                let start = get_readable_duration(chapter.start, DurationStyle::Compact);
+                let title = match Some(idx) == current {
+                    true => Span::from(chapter.title.clone()).fg(theme.text_secondary),
+                    false => Span::from(chapter.title.clone()),
+                };
+
+                Line::from_iter([Span::from(format!("{start:>8}  ")), title])
+            })
+            .collect::<Vec<_>>();
+
+        let list = List::new(chapters)
+            .fg(theme.text_muted)
+            .highlight_symbol(SELECTOR)
+            .highlight_style(theme.accent);
+
+        StatefulWidget::render(list, inner, buf, &mut state.popup.selection);
+    }
+}
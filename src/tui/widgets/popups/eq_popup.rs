@@ -0,0 +1,94 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Layout},
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, List, ListItem, Paragraph, StatefulWidget, Widget},
+};
+
+use crate::{
+    player::{EQ_FREQUENCIES, EQ_MAX_GAIN},
+    tui::widgets::{POPUP_PADDING, SELECTOR},
+    ui_state::UiState,
+};
+
+// Odd, so 0dB lands on a single center cell
+const BAR_WIDTH: usize = 25;
+
+pub struct EqualizerPopup;
+impl StatefulWidget for EqualizerPopup {
+    type State = UiState;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let theme = state.theme_manager.get_display_theme(true);
+        let settings = state.get_eq_settings();
+
+        let block = Block::bordered()
+            .border_type(theme.border_type)
+            .border_style(theme.border)
+            .title(format!(" Equalizer - {} ", state.get_eq_label()))
+            .title_bottom(" [h/l] gain / [Tab] preset / [r]evert / [Esc] ")
+            .title_alignment(Alignment::Center)
+            .padding(POPUP_PADDING)
+            .bg(theme.bg);
+
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let chunks = Layout::vertical([Constraint::Length(2), Constraint::Fill(1)]).split(inner);
+
+        Paragraph::new(format!("Preamp {:+.1} dB", settings.preamp))
+            .fg(theme.text_muted)
+            .centered()
+            .render(chunks[0], buf);
+
+        let bands = EQ_FREQUENCIES
+            .iter()
+            .zip(settings.gains)
+            .map(|(freq, db)| {
+                let (cut, boost) = bar_cells(db);
+                let half = BAR_WIDTH / 2;
+
+                ListItem::new(Line::from_iter([
+                    Span::from(format!("{:>5} ", freq_label(*freq))),
+                    Span::from("─".repeat(half - cut)),
+                    Span::from("━".repeat(cut)).fg(theme.accent),
+                    Span::from("┃").fg(theme.text_secondary),
+                    Span::from("━".repeat(boost)).fg(theme.accent),
+                    Span::from("─".repeat(half - boost)),
+                    Span::from(format!(" {db:+5.1} dB")),
+                ]))
+            })
+            .collect::<Vec<_>>();
+
+        let list = List::new(bands)
+            .fg(theme.text_muted)
+            .highlight_symbol(SELECTOR)
+            .highlight_style(theme.accent);
+
+        StatefulWidget::render(list, chunks[1], buf, &mut state.popup.selection);
+    }
+}
+
+fn freq_label(freq: f32) -> String {
+    match freq >= 1000.0 {
+        true => format!("{}k", freq / 1000.0),
+        false => format!("{freq}"),
+    }
+}
+
+/// Cells lit either side of the 0dB mark, as (cut, boost)
+fn bar_cells(db: f32) -> (usize, usize) {
+    let half = BAR_WIDTH / 2;
+    let lit = ((db.abs() / EQ_MAX_GAIN) * half as f32).round() as usize;
+    let lit = lit.min(half);
+
+    match db < 0.0 {
+        true => (lit, 0),
+        false => (0, lit),
+    }
+}
@@ -1,9 +1,17 @@
+mod chapter_popup;
+mod eq_popup;
 mod error;
 mod playlist_popup;
 mod root_manager;
+mod seek_popup;
+mod sleep_popup;
 mod theme_popup;
 
+pub use chapter_popup::ChapterPopup;
+pub use eq_popup::EqualizerPopup;
 pub use error::ErrorMsg;
 pub use playlist_popup::PlaylistPopup;
 pub use root_manager::RootManager;
+pub use seek_popup::SeekPopup;
+pub use sleep_popup::SleepPopup;
 pub use theme_popup::ThemeManager;
@@ -32,6 +32,7 @@ impl StatefulWidget for RootManager {
             Some(SettingsMode::ViewRoots) => " Settings - Music Library Roots ",
             Some(SettingsMode::AddRoot) => " Add New Root Directory ",
             Some(SettingsMode::RemoveRoot) => " Remove Root Directory ",
+            Some(SettingsMode::OutputDevice) => " Settings - Audio Output ",
             None => return,
         };
 
@@ -56,6 +57,7 @@ impl StatefulWidget for RootManager {
             Some(SettingsMode::ViewRoots) => render_roots_list(inner, buf, state),
             Some(SettingsMode::AddRoot) => render_add_root(inner, buf, state),
             Some(SettingsMode::RemoveRoot) => render_remove_root(inner, buf, state),
+            Some(SettingsMode::OutputDevice) => render_output_list(inner, buf, state),
             None => (),
         }
     }
@@ -64,9 +66,10 @@ impl StatefulWidget for RootManager {
 fn get_keymaps(mode: Option<&SettingsMode>) -> &'static str {
     if let Some(m) = mode {
         match m {
-            SettingsMode::ViewRoots => " [a]dd / [d]elete / [Esc] close ",
+            SettingsMode::ViewRoots => " [a]dd / [d]elete / [o]utput / [Esc] close ",
             SettingsMode::AddRoot => " [Enter] confirm / [Esc] cancel ",
             SettingsMode::RemoveRoot => " [Enter] confirm / [Esc] cancel ",
+            SettingsMode::OutputDevice => " [Enter] select / [Esc] back ",
         }
     } else {
         unreachable!()
@@ -106,6 +109,35 @@ fn render_roots_list(
     ratatui::prelude::StatefulWidget::render(list, area, buf, &mut state.popup.selection);
 }
 
+fn render_output_list(
+    area: ratatui::prelude::Rect,
+    buf: &mut ratatui::prelude::Buffer,
+    state: &mut UiState,
+) {
+    let theme = state.theme_manager.get_display_theme(true);
+    let active = state.get_output_device();
+
+    let items: Vec<Line> = state
+        .get_output_devices()
+        .iter()
+        .map(|device| {
+            let name = device.unwrap_or("System Default").to_string();
+            match *device == active {
+                true => Line::from_iter([name.into(), " (active)".fg(theme.accent)]),
+                false => Line::from(name),
+            }
+        })
+        .collect();
+
+    let list = List::new(items)
+        .fg(theme.text_muted)
+        .highlight_symbol(SELECTOR)
+        .highlight_style(Style::new().fg(theme.selection))
+        .highlight_spacing(HighlightSpacing::Always);
+
+    ratatui::prelude::StatefulWidget::render(list, area, buf, &mut state.popup.selection);
+}
+
 fn render_add_root(
     area: ratatui::prelude::Rect,
     buf: &mut ratatui::prelude::Buffer,
@@ -0,0 +1,68 @@
+use crate::{
+    DurationStyle, get_readable_duration,
+    library::SongInfo,
+    ui_state::{Pane, UiState},
+};
+use ratatui::{
+    layout::{Alignment, Constraint, Layout},
+    style::{Style, Stylize},
+    widgets::{Block, BorderType, Padding, Paragraph, StatefulWidget, Widget},
+};
+
+pub struct SeekPopup;
+impl StatefulWidget for SeekPopup {
+    type State = UiState;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let focus = matches!(state.get_pane(), Pane::Popup);
+        let theme = state.theme_manager.get_display_theme(focus);
+        let padding_h = (area.height as f32 * 0.2) as u16;
+        let padding_w = (area.width as f32 * 0.15) as u16;
+
+        let block = Block::bordered()
+            .border_type(theme.border_type)
+            .border_style(theme.border)
+            .title(" Seek To ")
+            .title_bottom(" [Enter] confirm / [Esc] cancel ")
+            .title_alignment(Alignment::Center)
+            .padding(Padding {
+                left: padding_w,
+                right: padding_w,
+                top: padding_h,
+                bottom: 0,
+            })
+            .fg(theme.accent)
+            .bg(theme.bg);
+
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let chunks = Layout::vertical([Constraint::Max(2), Constraint::Length(3)]).split(inner);
+
+        let elapsed = get_readable_duration(state.get_playback_elapsed(), DurationStyle::Compact);
+        let total = state
+            .get_now_playing()
+            .map(|s| s.get_duration_str())
+            .unwrap_or_default();
+
+        Paragraph::new(format!("{elapsed} / {total}"))
+            .centered()
+            .render(chunks[0], buf);
+
+        state.popup.input.set_block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .padding(Padding::horizontal(2)),
+        );
+        state
+            .popup
+            .input
+            .set_style(Style::new().fg(theme.text_primary));
+        state.popup.input.render(chunks[1], buf);
+    }
+}
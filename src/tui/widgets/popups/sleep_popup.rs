@@ -0,0 +1,67 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Layout},
+    style::Stylize,
+    widgets::{Block, List, Paragraph, StatefulWidget, Widget},
+};
+
+use crate::{
+    DurationStyle, get_readable_duration,
+    tui::widgets::{POPUP_PADDING, SELECTOR},
+    ui_state::{SLEEP_CHOICES, UiState},
+};
+
+pub struct SleepPopup;
+impl StatefulWidget for SleepPopup {
+    type State = UiState;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let theme = state.theme_manager.get_display_theme(true);
+        let fade = match state.sleep_fade {
+            true => "on",
+            false => "off",
+        };
+
+        let block = Block::bordered()
+            .border_type(theme.border_type)
+            .border_style(theme.border)
+            .title(" Sleep Timer ")
+            .title_bottom(format!(
+                " [Enter] start / [f]ade: {fade} / [c]ancel / [Esc] "
+            ))
+            .title_alignment(Alignment::Center)
+            .padding(POPUP_PADDING)
+            .bg(theme.bg);
+
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let chunks = Layout::vertical([Constraint::Length(2), Constraint::Fill(1)]).split(inner);
+
+        let status = match state.get_sleep_remaining() {
+            Some(remaining) => format!(
+                "Stopping in {}",
+                get_readable_duration(remaining, DurationStyle::Compact)
+            ),
+            None => "No timer running".to_string(),
+        };
+
+        Paragraph::new(status)
+            .fg(theme.text_muted)
+            .centered()
+            .render(chunks[0], buf);
+
+        let choices = SLEEP_CHOICES.iter().map(|c| c.label()).collect::<Vec<_>>();
+
+        let list = List::new(choices)
+            .fg(theme.text_muted)
+            .highlight_symbol(SELECTOR)
+            .highlight_style(theme.accent);
+
+        StatefulWidget::render(list, chunks[1], buf, &mut state.popup.selection);
+    }
+}
@@ -0,0 +1,117 @@
+use crate::ui_state::{DisplayTheme, UiState};
+use ratatui::{
+    style::Stylize,
+    widgets::{
+        Block, Padding, StatefulWidget, Widget,
+        canvas::{Canvas, Context, Line},
+    },
+};
+
+/// Decibels below full scale covered by the meter
+const METER_RANGE: f32 = 48.0;
+/// Vertical lines per meter, enough to fill it at any width
+const METER_STEPS: usize = 400;
+/// Room left for the channel labels, as a share of the width
+const LABEL_WIDTH: f64 = 0.03;
+
+pub struct Meter;
+
+impl StatefulWidget for Meter {
+    type State = UiState;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let theme = &state.theme_manager.get_display_theme(true);
+        let window = state.get_tapped_samples();
+
+        if window.samples.is_empty() {
+            return;
+        }
+
+        let channels: &[(&str, usize)] = match window.channels {
+            1 => &[("M", 0)],
+            _ => &[("L", 0), ("R", 1)],
+        };
+
+        let levels = channels
+            .iter()
+            .map(|(label, idx)| {
+                let (peak, sum) = window
+                    .channel(*idx)
+                    .fold((0.0f32, 0.0f32), |(peak, sum), s| {
+                        (peak.max(s.abs()), sum + s * s)
+                    });
+                let rms = (sum / window.frames() as f32).sqrt();
+
+                (*label, to_position(rms), to_position(peak))
+            })
+            .collect::<Vec<_>>();
+
+        let v_marg = match area.height > 20 {
+            true => ((area.height as f32) * 0.3) as u16,
+            false => 0,
+        };
+
+        let elapsed = state.get_playback_elapsed_f32();
+
+        Canvas::default()
+            .x_bounds([0.0, 1.0])
+            .y_bounds([0.0, levels.len() as f64])
+            .marker(theme.oscilloscope_style)
+            .paint(|ctx| {
+                // First channel on top
+                for (row, (label, rms, peak)) in levels.iter().rev().enumerate() {
+                    draw_meter(ctx, row as f64, *rms, *peak, elapsed, theme);
+                    ctx.print(0.0, row as f64 + 0.5, label.fg(theme.text_muted));
+                }
+            })
+            .background_color(theme.bg_global)
+            .block(Block::new().bg(theme.bg_global).padding(Padding {
+                left: 1,
+                right: 1,
+                top: v_marg,
+                // The bottom row belongs to the timer
+                bottom: v_marg.max(1),
+            }))
+            .render(area, buf);
+    }
+}
+
+/// Fraction of the meter a linear amplitude reaches
+fn to_position(amplitude: f32) -> f32 {
+    let db = 20.0 * amplitude.max(f32::EPSILON).log10();
+    ((db + METER_RANGE) / METER_RANGE).clamp(0.0, 1.0)
+}
+
+/// Fills the row up to the RMS level, and marks the peak with a single line
+fn draw_meter(ctx: &mut Context, row: f64, rms: f32, peak: f32, time: f32, theme: &DisplayTheme) {
+    let (bottom, top) = (row + 0.2, row + 0.8);
+    let x = |position: f32| LABEL_WIDTH + position as f64 * (1.0 - LABEL_WIDTH);
+
+    let filled = (rms * METER_STEPS as f32) as usize;
+    for step in 0..filled {
+        let position = step as f32 / METER_STEPS as f32;
+
+        ctx.draw(&Line {
+            x1: x(position),
+            y1: bottom,
+            x2: x(position),
+            y2: top,
+            color: theme.get_focused_color(position, time),
+        });
+    }
+
+    if peak > 0.0 {
+        ctx.draw(&Line {
+            x1: x(peak),
+            y1: bottom,
+            x2: x(peak),
+            y2: top,
+            color: theme.text_primary,
+        });
+    }
+}
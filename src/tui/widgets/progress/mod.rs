@@ -1,11 +1,15 @@
+mod meter;
 mod oscilloscope;
 mod progress_bar;
+mod spectrum;
 mod timer;
+mod vectorscope;
 mod waveform;
 
 use crate::{
     tui::widgets::progress::{
-        oscilloscope::Oscilloscope, progress_bar::ProgressBar, timer::Timer, waveform::Waveform,
+        meter::Meter, oscilloscope::Oscilloscope, progress_bar::ProgressBar, spectrum::Spectrum,
+        timer::Timer, vectorscope::Vectorscope, waveform::Waveform,
     },
     ui_state::{ProgressDisplay, UiState},
 };
@@ -13,6 +17,11 @@ use ratatui::widgets::StatefulWidget;
 
 pub(crate) const DEFAULT_AMP: f32 = 1.0;
 
+/// Unplayed parts of the A-B loop stand out from the rest of the track
+fn in_loop(ab_loop: Option<(f32, Option<f32>)>, position: f32) -> bool {
+    matches!(ab_loop, Some((a, Some(b))) if (a..=b).contains(&position))
+}
+
 pub struct Progress;
 impl StatefulWidget for Progress {
     type State = UiState;
@@ -26,11 +35,14 @@ impl StatefulWidget for Progress {
             Timer.render(area, buf, state);
             match &state.get_progress_display() {
                 ProgressDisplay::ProgressBar => ProgressBar.render(area, buf, state),
-                ProgressDisplay::Waveform => match !state.get_waveform_as_slice().is_empty() {
+                ProgressDisplay::Waveform => match state.has_waveform() {
                     true => Waveform.render(area, buf, state),
                     false => Oscilloscope.render(area, buf, state),
                 },
                 ProgressDisplay::Oscilloscope => Oscilloscope.render(area, buf, state),
+                ProgressDisplay::Spectrum => Spectrum.render(area, buf, state),
+                ProgressDisplay::Meter => Meter.render(area, buf, state),
+                ProgressDisplay::Vectorscope => Vectorscope.render(area, buf, state),
             }
         }
     }
@@ -19,7 +19,7 @@ impl StatefulWidget for Oscilloscope {
         state: &mut Self::State,
     ) {
         let theme = &state.theme_manager.get_display_theme(true);
-        let samples = state.get_tapped_samples();
+        let samples = state.get_tapped_samples().mono();
 
         if samples.is_empty() {
             return;
@@ -1,5 +1,6 @@
-use crate::{library::SongInfo, ui_state::UiState};
+use crate::{library::SongInfo, tui::widgets::CHAPTER_MARKER, ui_state::UiState};
 use ratatui::{
+    layout::Rect,
     style::Stylize,
     widgets::{Block, LineGauge, Padding, StatefulWidget, Widget},
 };
@@ -43,5 +44,42 @@ impl StatefulWidget for ProgressBar {
             .ratio(ratio as f64);
 
         guage.render(area, buf);
+
+        let inner = Rect {
+            x: area.x + 2,
+            y: area.y + (area.height / 2),
+            width: area.width.saturating_sub(5),
+            height: 1,
+        };
+
+        // Recolour the gauge cells inside the loop, the gauge can't do it
+        if let Some((a, b)) = state.get_loop_ratios() {
+            for x in inner.left()..inner.right() {
+                let position = (x - inner.x) as f32 / inner.width as f32;
+                let is_marker = [Some(a), b]
+                    .iter()
+                    .flatten()
+                    .any(|p| (p * inner.width as f32) as u16 == x - inner.x);
+
+                let in_loop = position >= ratio && super::in_loop(Some((a, b)), position);
+                if (is_marker || in_loop)
+                    && let Some(cell) = buf.cell_mut((x, inner.y))
+                {
+                    cell.set_fg(theme.accent);
+                }
+            }
+        }
+
+        // Chapter boundaries are marked on the row above the gauge
+        if inner.y > area.y {
+            for chapter in state.get_chapter_ratios() {
+                let x = inner.x + (chapter * inner.width as f32) as u16;
+                if x < inner.right()
+                    && let Some(cell) = buf.cell_mut((x, inner.y - 1))
+                {
+                    cell.set_symbol(CHAPTER_MARKER).set_fg(theme.text_muted);
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,72 @@
+use crate::ui_state::UiState;
+use ratatui::{layout::Rect, widgets::StatefulWidget};
+
+const BAR_WIDTH: u16 = 2;
+const BAR_GAP: u16 = 1;
+
+const LEVELS: [&str; 8] = ["▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
+const PEAK: &str = "▔";
+
+pub struct Spectrum;
+
+impl StatefulWidget for Spectrum {
+    type State = UiState;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        // The bottom row belongs to the timer
+        let area = Rect {
+            x: area.x + 1,
+            width: area.width.saturating_sub(2),
+            height: area.height.saturating_sub(1),
+            ..area
+        };
+
+        if area.is_empty() {
+            return;
+        }
+
+        let bands = ((area.width + BAR_GAP) / (BAR_WIDTH + BAR_GAP)).max(1);
+        state.update_spectrum(bands as usize);
+
+        let theme = state.theme_manager.get_display_theme(true);
+        let spectrum = state.get_spectrum();
+        let elapsed = state.get_playback_elapsed_f32();
+
+        // Centre the bars, the division rarely comes out even
+        let used = (bands * (BAR_WIDTH + BAR_GAP) - BAR_GAP).min(area.width);
+        let left = area.x + (area.width - used) / 2;
+        let rows = area.height as usize;
+
+        for (idx, (bar, peak)) in spectrum.bars().iter().zip(spectrum.peaks()).enumerate() {
+            let position = idx as f32 / (bands - 1).max(1) as f32;
+            let color = theme.get_focused_color(position, elapsed);
+
+            // Heights in eighths of a cell
+            let height = (bar * (rows * 8) as f32).round() as usize;
+            let peak_row = (peak.level * rows as f32).ceil() as usize;
+
+            let x = left + idx as u16 * (BAR_WIDTH + BAR_GAP);
+            let bar_area = Rect::new(x, area.y, BAR_WIDTH, area.height).intersection(area);
+
+            for row in 0..rows {
+                let y = area.bottom() - 1 - row as u16;
+                let fill = height.saturating_sub(row * 8).min(8);
+
+                let symbol = match fill {
+                    0 if peak_row == row + 1 => PEAK,
+                    0 => continue,
+                    fill => LEVELS[fill - 1],
+                };
+
+                for x in bar_area.left()..bar_area.right() {
+                    buf[(x, y)].set_symbol(symbol).set_fg(color);
+                }
+            }
+        }
+    }
+}
@@ -43,5 +43,22 @@ impl StatefulWidget for Timer {
             .fg(text_color)
             .right_aligned()
             .render(Rect::new(x_pos, y_pos, DUR_WIDTH, 1), buf);
+
+        let speed = state.get_playback_speed();
+        if speed != 1.0 {
+            // The waveform fills the row beside the elapsed time, so the
+            // speed sits in the margin underneath it instead
+            let speed_rect = match state.get_progress_display() {
+                ProgressDisplay::Waveform => Rect::new(3, y_pos + 1, DUR_WIDTH, 1),
+                _ => Rect::new(3 + DUR_WIDTH + 1, y_pos, DUR_WIDTH, 1),
+            };
+
+            if speed_rect.bottom() <= area.bottom() {
+                Text::from(format!("{speed}x"))
+                    .fg(text_color)
+                    .left_aligned()
+                    .render(speed_rect, buf);
+            }
+        }
     }
 }
@@ -0,0 +1,82 @@
+use crate::ui_state::{DisplayTheme, UiState};
+use ratatui::{
+    style::Stylize,
+    widgets::{
+        Block, Padding, StatefulWidget, Widget,
+        canvas::{Canvas, Context, Line},
+    },
+};
+
+pub struct Vectorscope;
+
+impl StatefulWidget for Vectorscope {
+    type State = UiState;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let theme = &state.theme_manager.get_display_theme(true);
+        let window = state.get_tapped_samples();
+
+        if window.samples.is_empty() {
+            return;
+        }
+
+        // Rotated by 45 degrees, so that mono sits on the vertical axis and
+        // out of phase material spreads out sideways
+        let points = window
+            .channel(0)
+            .zip(window.channel(1))
+            .map(|(l, r)| ((r - l) as f64, (l + r) as f64))
+            .collect::<Vec<_>>();
+
+        let peak = points
+            .iter()
+            .map(|(x, y)| x.abs().max(y.abs()))
+            .fold(1.0, f64::max);
+
+        // Cells are about twice as tall as they are wide, which would squash
+        // the trace without widening the x axis to match
+        let aspect = (area.width as f64 / (area.height.max(1) as f64 * 2.0)).max(1.0);
+        let elapsed = state.get_playback_elapsed_f32();
+
+        Canvas::default()
+            .x_bounds([-peak * aspect, peak * aspect])
+            .y_bounds([-peak, peak])
+            .marker(theme.oscilloscope_style)
+            .paint(|ctx| {
+                draw_vectorscope(ctx, &points, elapsed, theme);
+            })
+            .background_color(theme.bg_global)
+            .block(Block::new().bg(theme.bg_global).padding(Padding {
+                left: 1,
+                right: 1,
+                top: 0,
+                // The bottom row belongs to the timer
+                bottom: 1,
+            }))
+            .render(area, buf);
+    }
+}
+
+/// Joins the points in the order they were played, the gradient running
+/// from the oldest to the newest
+fn draw_vectorscope(ctx: &mut Context, points: &[(f64, f64)], time: f32, theme: &DisplayTheme) {
+    for (i, pair) in points.windows(2).enumerate() {
+        let progress = i as f32 / points.len() as f32;
+
+        let time = time / 4.0; // Slow down gradient scroll substantially
+        let color = theme.get_focused_color(progress, time);
+
+        ctx.draw(&Line {
+            x1: pair[0].0,
+            y1: pair[0].1,
+            x2: pair[1].0,
+            y2: pair[1].1,
+            color,
+        });
+    }
+}
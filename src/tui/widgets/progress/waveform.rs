@@ -1,4 +1,7 @@
-use crate::{library::SongInfo, tui::widgets::WAVEFORM_WIDGET_HEIGHT, ui_state::UiState};
+use crate::{
+    tui::widgets::WAVEFORM_WIDGET_HEIGHT,
+    ui_state::{Mode, UiState},
+};
 use ratatui::{
     style::{Color, Stylize},
     widgets::{
@@ -7,6 +10,9 @@ use ratatui::{
     },
 };
 
+/// Length of the chapter ticks, as a share of the waveform's height
+const CHAPTER_TICK: f64 = 0.2;
+
 pub struct Waveform;
 impl StatefulWidget for Waveform {
     type State = UiState;
@@ -26,13 +32,23 @@ impl StatefulWidget for Waveform {
             _ => (area.height as f32 * 0.35) as u16,
         };
 
-        let np = &state
-            .get_now_playing()
-            .expect("Expected a song to be playing. [Widget: Waveform]");
+        let zoomed = state.get_mode() == Mode::Fullscreen;
+        let Some(view) = state.get_waveform_view(zoomed) else {
+            return;
+        };
+
+        let wf_len = view.points.len();
+        let span = view.end - view.start;
+        let ab_loop = state.get_loop_ratios();
+        let chapters = state.get_chapter_ratios();
+        let zoom = state.get_waveform_zoom();
 
-        let waveform = state.get_waveform_as_slice();
-        let wf_len = waveform.len();
-        let duration_f32 = &np.get_duration_f32();
+        // Track positions to canvas x, `None` when out of view
+        let to_x = |ratio: f32| {
+            (view.start..=view.end)
+                .contains(&ratio)
+                .then(|| ((ratio - view.start) / span * wf_len as f32) as f64)
+        };
 
         Canvas::default()
             .x_bounds([0.0, wf_len as f64])
@@ -40,22 +56,45 @@ impl StatefulWidget for Waveform {
             .marker(theme.waveform_style)
             .paint(|ctx| {
                 let elapsed = state.get_playback_elapsed_f32();
-                let progress = elapsed / duration_f32;
+                let progress = view.progress;
+
+                for (idx, (min, max)) in view.points.iter().enumerate() {
+                    let low = (*min as f64 * WAVEFORM_WIDGET_HEIGHT).round();
+                    let high = (*max as f64 * WAVEFORM_WIDGET_HEIGHT).round();
 
-                for (idx, amp) in waveform.iter().enumerate() {
-                    let hgt = (*amp as f64 * WAVEFORM_WIDGET_HEIGHT).round();
+                    // Colors follow the view, so that the gradient still
+                    // spans the whole width when zoomed in
                     let position = idx as f32 / wf_len as f32;
+                    let track_position = view.start + position * span;
+                    let amp = (max - min) / 2.0;
 
-                    let color = match position < progress {
+                    let color = match track_position < progress {
                         true => theme.get_focused_color(position, elapsed),
-                        false => theme.get_inactive_color(position, elapsed, *amp),
+                        false if super::in_loop(ab_loop, track_position) => theme.accent,
+                        false => theme.get_inactive_color(position, elapsed, amp),
                     };
 
                     match area.width < 170 {
-                        true => draw_waveform_line(ctx, idx as f64, hgt, color),
-                        false => draw_waveform_rect(ctx, idx as f64, hgt, color),
+                        true => draw_waveform_line(ctx, idx as f64, low, high, color),
+                        false => draw_waveform_rect(ctx, idx as f64, low, high, color),
+                    }
+                }
+
+                if let Some((a, b)) = ab_loop {
+                    for x in std::iter::once(a).chain(b).filter_map(to_x) {
+                        let edge = WAVEFORM_WIDGET_HEIGHT;
+                        draw_waveform_line(ctx, x, -edge, edge, theme.accent);
                     }
                 }
+
+                for x in chapters.iter().copied().filter_map(to_x) {
+                    draw_chapter_ticks(ctx, x, theme.text_muted);
+                }
+
+                if zoomed && zoom > 1 {
+                    let label = format!("{zoom}x");
+                    ctx.print(0.0, WAVEFORM_WIDGET_HEIGHT, label.fg(theme.text_muted));
+                }
             })
             .background_color(theme.bg_global)
             .block(Block::new().bg(theme.bg_global).padding(Padding {
@@ -70,24 +109,39 @@ impl StatefulWidget for Waveform {
 
 /// Lines create a more detailed and cleaner look
 /// especially when seen in smaller windows
-fn draw_waveform_line(ctx: &mut Context, idx: f64, hgt: f64, color: Color) {
+fn draw_waveform_line(ctx: &mut Context, idx: f64, low: f64, high: f64, color: Color) {
     ctx.draw(&Line {
         x1: idx,
         x2: idx,
-        y1: hgt,
-        y2: hgt * -1.0,
+        y1: high,
+        y2: low,
         color,
     })
 }
 
+/// Chapter boundaries are notched into the top and bottom edges, leaving
+/// full height lines to the A-B loop
+fn draw_chapter_ticks(ctx: &mut Context, idx: f64, color: Color) {
+    let tick = WAVEFORM_WIDGET_HEIGHT * CHAPTER_TICK;
+    for edge in [WAVEFORM_WIDGET_HEIGHT, -WAVEFORM_WIDGET_HEIGHT] {
+        ctx.draw(&Line {
+            x1: idx,
+            x2: idx,
+            y1: edge,
+            y2: edge - tick * edge.signum(),
+            color,
+        })
+    }
+}
+
 /// Rectangles cleanly extend the waveform when in
 /// full-screen view
-fn draw_waveform_rect(ctx: &mut Context, idx: f64, hgt: f64, color: Color) {
+fn draw_waveform_rect(ctx: &mut Context, idx: f64, low: f64, high: f64, color: Color) {
     ctx.draw(&Rectangle {
         x: idx,
-        y: hgt * -1.0,
-        width: 0.5,        // This makes the waveform cleaner on resize
-        height: hgt * 2.0, // Rectangles are drawn from the bottom
+        y: low,
+        width: 0.5,         // This makes the waveform cleaner on resize
+        height: high - low, // Rectangles are drawn from the bottom
         color,
     });
 }
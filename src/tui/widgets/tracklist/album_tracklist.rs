@@ -1,7 +1,9 @@
 use crate::{
     library::SongInfo,
     truncate_at_last_space,
-    tui::widgets::tracklist::{CellFactory, create_empty_block, create_standard_table},
+    tui::widgets::tracklist::{
+        CellFactory, create_empty_block, create_standard_table, visible_window,
+    },
     ui_state::{Pane, UiState},
 };
 use ratatui::{
@@ -20,24 +22,30 @@ impl StatefulWidget for AlbumView {
         state: &mut Self::State,
     ) {
         let focus = matches!(state.get_pane(), Pane::TrackList);
-        let theme = &state.theme_manager.get_display_theme(focus);
 
         if state.albums.is_empty() {
+            let theme = state.theme_manager.get_display_theme(focus);
             create_empty_block(theme, "0 Songs").render(area, buf);
             return;
         }
 
+        let len = match state.get_selected_album() {
+            Some(album) => album.tracklist.len(),
+            None => state.albums[0].tracklist.len(),
+        };
+        let (visible, mut window) = visible_window(state, focus, len, area);
+
+        let theme = &state.theme_manager.get_display_theme(focus);
         let album = state.get_selected_album().unwrap_or(&state.albums[0]);
         let album_title = match &album.title.is_empty() {
             true => String::from("[Unknown Album]"),
             false => truncate_at_last_space(&album.title, (area.width / 3) as usize),
         };
 
-        let rows = album
-            .tracklist
+        let rows = album.tracklist[visible.clone()]
             .iter()
-            .enumerate()
-            .map(|(idx, song)| {
+            .zip(visible)
+            .map(|(song, idx)| {
                 let is_m_selected = state.get_multi_select_indices().contains(&idx);
 
                 let track_no = CellFactory::get_track_discs(theme, song, is_m_selected);
@@ -73,6 +81,6 @@ impl StatefulWidget for AlbumView {
         ]);
 
         let table = create_standard_table(rows, title, state, theme);
-        StatefulWidget::render(table, area, buf, &mut state.display_state.table_pos);
+        StatefulWidget::render(table, area, buf, &mut window);
     }
 }
@@ -1,6 +1,6 @@
 use crate::{
     library::SongInfo,
-    tui::widgets::tracklist::{CellFactory, create_standard_table, get_title},
+    tui::widgets::tracklist::{CellFactory, create_standard_table, get_title, visible_window},
     ui_state::{Pane, UiState},
 };
 use ratatui::{
@@ -18,13 +18,16 @@ impl StatefulWidget for GenericView {
         state: &mut Self::State,
     ) {
         let focus = matches!(state.get_pane(), Pane::TrackList);
+        let len = state.get_legal_songs().len();
+        let (visible, mut window) = visible_window(state, focus, len, area);
+
         let theme = &state.theme_manager.get_display_theme(focus);
-        let songs = state.get_legal_songs();
+        let songs = &state.get_legal_songs()[visible.clone()];
 
         let rows = songs
             .iter()
-            .enumerate()
-            .map(|(idx, song)| {
+            .zip(visible)
+            .map(|(song, idx)| {
                 let is_multi_selected = state.get_multi_select_indices().contains(&idx);
 
                 let index = CellFactory::index_cell(&theme, idx, is_multi_selected);
@@ -46,6 +49,6 @@ impl StatefulWidget for GenericView {
         let title = get_title(state, area);
 
         let table = create_standard_table(rows, title, state, theme);
-        StatefulWidget::render(table, area, buf, &mut state.display_state.table_pos);
+        StatefulWidget::render(table, area, buf, &mut window);
     }
 }
@@ -4,6 +4,7 @@ mod search_results;
 
 use std::{
     collections::HashMap,
+    ops::Range,
     sync::{Arc, LazyLock},
 };
 
@@ -15,14 +16,14 @@ use crate::{
     DurationStyle, get_readable_duration,
     library::{SimpleSong, SongInfo},
     truncate_at_last_space,
-    tui::widgets::{MUSIC_NOTE, QUEUED},
+    tui::widgets::{MUSIC_NOTE, PARTIALLY_PLAYED, QUEUED},
     ui_state::{DisplayTheme, LibraryView, Mode, Pane, UiState},
 };
 use ratatui::{
     layout::{Alignment, Constraint, Flex, Rect},
     style::{Color, Style, Stylize},
     text::{Line, Span, Text},
-    widgets::{Block, Cell, Padding, Row, Table},
+    widgets::{Block, Cell, Padding, Row, Table, TableState},
 };
 
 const COLUMN_SPACING: u16 = 2;
@@ -106,6 +107,43 @@ pub fn create_standard_table<'a>(
         .row_highlight_style(highlight_style)
 }
 
+/// Rows are only built for the part of the table in view, which is what
+/// keeps the whole library in power mode cheap to draw. Scrolls the stored
+/// offset to keep the selection in view, and returns the range of rows to
+/// build alongside a table state relative to it.
+pub(super) fn visible_window(
+    state: &mut UiState,
+    focus: bool,
+    len: usize,
+    area: Rect,
+) -> (Range<usize>, TableState) {
+    // Same borders, padding and titles as `create_standard_table`
+    let borders = state.theme_manager.get_display_theme(focus).border_display;
+    let block = Block::bordered()
+        .borders(borders)
+        .title_top("")
+        .title_bottom("")
+        .padding(PADDING);
+    let height = (block.inner(area).height as usize).max(1);
+
+    let table_pos = &mut state.display_state.table_pos;
+    let selected = table_pos.selected().map(|s| s.min(len.saturating_sub(1)));
+    let mut offset = table_pos.offset().min(len.saturating_sub(height));
+
+    if let Some(selected) = selected {
+        if selected < offset {
+            offset = selected;
+        } else if selected >= offset + height {
+            offset = selected + 1 - height;
+        }
+    }
+
+    *table_pos.offset_mut() = offset;
+
+    let window = TableState::default().with_selected(selected.map(|s| s - offset));
+    (offset..(offset + height).min(len), window)
+}
+
 pub fn create_empty_block(theme: &DisplayTheme, title: &str) -> Block<'static> {
     Block::bordered()
         .borders(theme.border_display)
@@ -137,6 +175,13 @@ impl CellFactory {
                 true => theme.text_selected,
                 false => theme.text_secondary,
             })
+        } else if let Some(progress) = state.get_resume_progress(song) {
+            // Quarter, half or three quarters through
+            let idx = ((progress * 4.0).round() as usize).clamp(1, 3) - 1;
+            PARTIALLY_PLAYED[idx].fg(match ms {
+                true => theme.text_selected,
+                false => theme.text_muted,
+            })
         } else {
             "".into()
         })
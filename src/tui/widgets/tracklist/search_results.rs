@@ -1,6 +1,6 @@
 use crate::{
     library::SongInfo,
-    tui::widgets::tracklist::{CellFactory, create_standard_table},
+    tui::widgets::tracklist::{CellFactory, create_standard_table, visible_window},
     ui_state::{MatchField, Pane, UiState, fade_color},
 };
 use ratatui::{
@@ -19,10 +19,11 @@ impl StatefulWidget for StandardTable {
         state: &mut Self::State,
     ) {
         let focus = matches!(state.get_pane(), Pane::TrackList | Pane::Search);
-        let theme = &state.theme_manager.get_display_theme(focus);
+        let song_len = state.get_legal_songs().len();
+        let (visible, mut window) = visible_window(state, focus, song_len, area);
 
-        let songs = state.get_legal_songs().to_owned();
-        let song_len = songs.len();
+        let theme = &state.theme_manager.get_display_theme(focus);
+        let songs = &state.get_legal_songs()[visible];
         let search_len = state.get_search_len();
 
         let title = match state.get_mode() {
@@ -57,6 +58,6 @@ impl StatefulWidget for StandardTable {
 
         let table = create_standard_table(rows, title.into(), state, theme);
 
-        StatefulWidget::render(table, area, buf, &mut state.display_state.table_pos);
+        StatefulWidget::render(table, area, buf, &mut window);
     }
 }
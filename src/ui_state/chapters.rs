@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use crate::{
+    library::{Chapter, SongInfo},
+    ui_state::{PopupType, UiState},
+};
+
+/// Going back this far into a chapter restarts it, rather than skipping to
+/// the one before
+const CHAPTER_RESTART: Duration = Duration::from_secs(3);
+
+impl UiState {
+    pub(crate) fn load_chapters(&mut self, song_id: u64) {
+        self.chapters = self.db_worker.get_chapters(song_id).unwrap_or_default();
+    }
+
+    pub(crate) fn clear_chapters(&mut self) {
+        self.chapters.clear();
+    }
+
+    pub fn get_chapters(&self) -> &[Chapter] {
+        &self.chapters
+    }
+
+    /// Index of the chapter the playhead is in
+    pub fn get_current_chapter(&self) -> Option<usize> {
+        self.chapter_at(self.get_playback_elapsed())
+    }
+
+    fn chapter_at(&self, elapsed: Duration) -> Option<usize> {
+        self.chapters.iter().rposition(|c| c.start <= elapsed)
+    }
+
+    /// Chapter boundaries as fractions of the current track, leaving out
+    /// one which opens the track
+    pub fn get_chapter_ratios(&self) -> Vec<f32> {
+        let Some(duration) = self.get_now_playing().map(|s| s.get_duration_f32()) else {
+            return Vec::new();
+        };
+
+        self.chapters
+            .iter()
+            .filter(|c| !c.start.is_zero())
+            .map(|c| (c.start.as_secs_f32() / duration).min(1.0))
+            .collect()
+    }
+
+    pub fn next_chapter_start(&self) -> Option<Duration> {
+        let elapsed = self.get_playback_elapsed();
+        self.chapters
+            .iter()
+            .find(|c| c.start > elapsed)
+            .map(|c| c.start)
+    }
+
+    pub fn prev_chapter_start(&self) -> Option<Duration> {
+        // The playhead keeps moving, so both are worked out from one reading
+        let elapsed = self.get_playback_elapsed();
+        let current = self.chapter_at(elapsed)?;
+        let into_chapter = elapsed.saturating_sub(self.chapters[current].start);
+
+        match into_chapter > CHAPTER_RESTART || current == 0 {
+            true => Some(self.chapters[current].start),
+            false => Some(self.chapters[current - 1].start),
+        }
+    }
+
+    pub fn open_chapters(&mut self) {
+        if self.chapters.is_empty() {
+            return;
+        }
+
+        let current = self.get_current_chapter().unwrap_or(0);
+        self.popup.selection.select(Some(current));
+        self.show_popup(PopupType::Chapters);
+    }
+
+    pub fn get_chapter_choice(&self) -> Option<Duration> {
+        let idx = self.popup.selection.selected()?;
+        self.chapters.get(idx).map(|c| c.start)
+    }
+}
@@ -2,7 +2,8 @@ use super::{AlbumSort, LibraryView, Mode, Pane, TableSort, UiState};
 use crate::{
     key_handler::Director,
     library::{Album, Playlist, SimpleSong, SongInfo},
-    ui_state::{PopupType, ProgressDisplay},
+    player::EQ_BANDS,
+    ui_state::{PopupType, ProgressDisplay, SLEEP_CHOICES, SettingsMode},
 };
 use anyhow::{Context, Result, anyhow, bail};
 use indexmap::IndexSet;
@@ -26,6 +27,9 @@ pub struct DisplayState {
     table_pos_cached: usize,
 
     pub multi_select: IndexSet<usize>,
+
+    /// Whether the last frame drew the progress display
+    pub progress_visible: bool,
 }
 
 impl DisplayState {
@@ -47,6 +51,8 @@ impl DisplayState {
             table_pos_cached: 0,
 
             multi_select: IndexSet::default(),
+
+            progress_visible: true,
         }
     }
 }
@@ -145,6 +151,7 @@ impl UiState {
                 let history_ids = self.playback.export_history();
 
                 let _ = self.save_state();
+                let _ = self.save_session();
                 let _ = self.db_worker.save_history_to_db(history_ids);
 
                 self.display_state.mode = Mode::QUIT;
@@ -432,9 +439,13 @@ impl UiState {
         let popup_type = &self.popup.current;
 
         let list_len = match popup_type {
+            PopupType::Settings(SettingsMode::OutputDevice) => self.get_output_devices().len(),
             PopupType::Settings(_) => self.get_roots().len(),
             PopupType::Playlist(_) => self.playlists.len(),
             PopupType::ThemeManager => self.theme_manager.theme_lib.len(),
+            PopupType::Equalizer => EQ_BANDS,
+            PopupType::SleepTimer => SLEEP_CHOICES.len(),
+            PopupType::Chapters => self.get_chapters().len(),
             _ => return,
         };
 
@@ -456,9 +467,13 @@ impl UiState {
         let popup_type = &self.popup.current;
 
         let list_len = match popup_type {
+            PopupType::Settings(SettingsMode::OutputDevice) => self.get_output_devices().len(),
             PopupType::Settings(_) => self.get_roots().len(),
             PopupType::Playlist(_) => self.playlists.len(),
             PopupType::ThemeManager => self.theme_manager.theme_lib.len(),
+            PopupType::Equalizer => EQ_BANDS,
+            PopupType::SleepTimer => SLEEP_CHOICES.len(),
+            PopupType::Chapters => self.get_chapters().len(),
             _ => return,
         };
 
@@ -0,0 +1,209 @@
+use anyhow::{Result, anyhow, bail};
+use serde::Deserialize;
+use std::path::Path;
+
+use crate::{
+    CONFIG_DIRECTORY, EQ_DIRECTORY,
+    key_handler::Incrementor,
+    player::{EQ_BANDS, EQ_MAX_GAIN, EqSettings},
+    ui_state::{PopupType, UiState},
+};
+
+const FLAT_PRESET: &str = "Flat";
+
+/// Layout of a preset file in the `equalizer` config directory
+#[derive(Deserialize)]
+struct EqPresetImport {
+    #[serde(default)]
+    preamp: f32,
+    bands: Vec<f32>,
+}
+
+#[derive(Clone)]
+pub struct EqPreset {
+    pub name: String,
+    pub settings: EqSettings,
+}
+
+impl EqPreset {
+    fn flat() -> Self {
+        EqPreset {
+            name: FLAT_PRESET.to_string(),
+            settings: EqSettings::default(),
+        }
+    }
+
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file_str = std::fs::read_to_string(path.as_ref())?;
+        let import = toml::from_str::<EqPresetImport>(&file_str)?;
+
+        if import.bands.len() != EQ_BANDS {
+            bail!("Expected {EQ_BANDS} bands, found {}", import.bands.len());
+        }
+
+        if !import.preamp.is_finite() || !import.bands.iter().all(|g| g.is_finite()) {
+            bail!("Preamp and band gains must be finite numbers");
+        }
+
+        let name = path
+            .as_ref()
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or(anyhow!("Could not identify preset name"))?
+            .to_string();
+
+        Ok(EqPreset {
+            name,
+            settings: EqSettings {
+                preamp: clamp_gain(import.preamp),
+                gains: std::array::from_fn(|i| clamp_gain(import.bands[i])),
+            },
+        })
+    }
+}
+
+pub struct EqManager {
+    pub presets: Vec<EqPreset>,
+    active: usize,
+    // Live settings, which drift from the active preset as bands are edited
+    current: EqSettings,
+}
+
+impl EqManager {
+    pub fn new() -> Self {
+        EqManager {
+            presets: Self::collect_presets(),
+            active: 0,
+            current: EqSettings::default(),
+        }
+    }
+
+    /// `Flat` always comes first, followed by the presets on disk
+    fn collect_presets() -> Vec<EqPreset> {
+        let mut presets = vec![EqPreset::flat()];
+        let eq_dir = dirs::config_dir().map(|dir| dir.join(CONFIG_DIRECTORY).join(EQ_DIRECTORY));
+
+        if let Some(ref eq_path) = eq_dir {
+            let _ = std::fs::create_dir_all(eq_path);
+
+            if let Ok(entries) = eq_path.read_dir() {
+                let mut found = entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("toml"))
+                    .filter_map(|path| EqPreset::load_from_file(&path).ok())
+                    .filter(|preset| preset.name != FLAT_PRESET)
+                    .collect::<Vec<_>>();
+
+                found.sort_by_key(|p| p.name.to_lowercase());
+                presets.extend(found);
+            }
+        }
+        presets
+    }
+
+    /// Re-read the presets from disk, keeping the active one if it still exists
+    pub fn update_presets(&mut self) {
+        let name = self.active_name().to_string();
+        self.presets = Self::collect_presets();
+        self.active = self.find_preset(&name).unwrap_or(0);
+    }
+
+    fn find_preset(&self, name: &str) -> Option<usize> {
+        self.presets.iter().position(|p| p.name == name)
+    }
+
+    pub fn active_name(&self) -> &str {
+        self.presets
+            .get(self.active)
+            .map(|p| p.name.as_str())
+            .unwrap_or(FLAT_PRESET)
+    }
+
+    pub fn is_modified(&self) -> bool {
+        self.presets
+            .get(self.active)
+            .is_some_and(|p| p.settings != self.current)
+    }
+
+    pub fn settings(&self) -> EqSettings {
+        self.current
+    }
+
+    pub fn select(&mut self, idx: usize) {
+        if let Some(preset) = self.presets.get(idx) {
+            self.active = idx;
+            self.current = preset.settings;
+        }
+    }
+
+    pub fn cycle(&mut self, dir: Incrementor) {
+        let len = self.presets.len();
+        let idx = match dir {
+            Incrementor::Up => (self.active + len - 1) % len,
+            Incrementor::Down => (self.active + 1) % len,
+        };
+        self.select(idx);
+    }
+
+    /// Discard live edits
+    pub fn revert(&mut self) {
+        self.select(self.active);
+    }
+
+    pub fn adjust_band(&mut self, band: usize, delta: f32) {
+        if let Some(gain) = self.current.gains.get_mut(band) {
+            *gain = clamp_gain(*gain + delta);
+        }
+    }
+
+    /// Restore the preset by name, then any live edits on top of it
+    pub fn restore(&mut self, name: &str, gains: &[f32]) {
+        self.select(self.find_preset(name).unwrap_or(0));
+
+        if gains.len() == EQ_BANDS {
+            self.current.gains = std::array::from_fn(|i| clamp_gain(gains[i]));
+        }
+    }
+}
+
+fn clamp_gain(db: f32) -> f32 {
+    match db.is_finite() {
+        true => db.clamp(-EQ_MAX_GAIN, EQ_MAX_GAIN),
+        false => 0.0,
+    }
+}
+
+impl UiState {
+    pub fn open_equalizer(&mut self) {
+        self.equalizer.update_presets();
+        self.popup.selection.select(Some(0));
+        self.show_popup(PopupType::Equalizer);
+    }
+
+    pub fn get_eq_settings(&self) -> EqSettings {
+        self.equalizer.settings()
+    }
+
+    /// Name of the active preset, marked when it has been edited
+    pub fn get_eq_label(&self) -> String {
+        match self.equalizer.is_modified() {
+            true => format!("{}*", self.equalizer.active_name()),
+            false => self.equalizer.active_name().to_string(),
+        }
+    }
+
+    pub fn adjust_eq_band(&mut self, delta: i8) {
+        if let Some(band) = self.popup.selection.selected() {
+            self.equalizer.adjust_band(band, delta as f32);
+        }
+    }
+
+    pub fn cycle_eq_preset(&mut self, dir: Incrementor) {
+        self.equalizer.cycle(dir);
+    }
+
+    pub fn revert_equalizer(&mut self) {
+        self.equalizer.revert();
+    }
+}
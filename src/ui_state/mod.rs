@@ -1,33 +1,41 @@
+mod chapters;
 mod display_state;
 mod domain;
+mod equalizer;
 mod multi_select;
 mod playlist;
 mod popup;
 mod progress_display;
+mod resume;
 mod search_state;
 mod settings;
+mod sleep_timer;
+mod spectrum;
 mod theme;
 mod ui_snapshot;
 mod ui_state;
 mod waveform;
 
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 pub use display_state::DisplayState;
 pub use domain::{AlbumSort, LibraryView, Mode, Pane, TableSort};
+pub use equalizer::EqManager;
 pub use playlist::PlaylistAction;
 pub use popup::PopupType;
 pub use progress_display::ProgressDisplay;
 pub use search_state::MatchField;
 pub use settings::SettingsMode;
+pub use sleep_timer::{SLEEP_CHOICES, SleepChoice};
+pub use spectrum::SpectrumState;
 pub use theme::DisplayTheme;
 pub use ui_snapshot::UiSnapshot;
-pub use waveform::WaveformManager;
+pub use waveform::{WaveformBatchStatus, WaveformManager, WaveformView, generate_waveform};
 
 use crate::{
     Library, PlaybackSession,
     database::DbWorker,
-    library::{Album, Playlist, SimpleSong},
+    library::{Album, Chapter, Playlist, SimpleSong},
     player::PlaybackMetrics,
     ui_state::{popup::PopupState, search_state::SearchState},
 };
@@ -42,17 +50,29 @@ pub struct UiState {
     search: SearchState,
     pub(crate) popup: PopupState,
     pub(crate) theme_manager: ThemeManager,
+    pub(crate) equalizer: EqManager,
     pub(crate) display_state: DisplayState,
 
     waveform: WaveformManager,
     progress_display: ProgressDisplay,
+    spectrum: SpectrumState,
 
     legal_songs: Vec<Arc<SimpleSong>>,
     pub(crate) albums: Vec<Album>,
     pub(crate) playlists: Vec<Playlist>,
 
+    output_devices: Vec<String>,
+    pub(crate) output_device: Option<String>,
+    pub(crate) sleep_fade: bool,
+
+    pub(crate) resume_after: Option<Duration>,
+    resume_positions: HashMap<u64, Duration>,
+
+    chapters: Vec<Chapter>,
+
     pub library_refresh_progress: Option<u8>,
     pub library_refresh_detail: Option<String>,
+    waveform_batch: Option<WaveformBatchStatus>,
 }
 
 pub use theme::*;
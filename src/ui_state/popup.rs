@@ -13,6 +13,10 @@ pub enum PopupType {
     Settings(SettingsMode),
     Playlist(PlaylistAction),
     ThemeManager,
+    SeekTo,
+    Equalizer,
+    SleepTimer,
+    Chapters,
 }
 
 pub struct PopupState {
@@ -47,6 +51,11 @@ impl PopupState {
                 self.input.select_all();
                 self.input.cut();
             }
+            PopupType::SeekTo => {
+                self.input.set_placeholder_text(" 1:30, 1:02:45 or 50% ");
+                self.input.select_all();
+                self.input.cut();
+            }
             PopupType::Settings(SettingsMode::AddRoot) => {
                 self.input
                     .set_placeholder_text(" Enter path to directory: ");
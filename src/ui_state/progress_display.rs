@@ -1,13 +1,15 @@
 use crate::{
     player::PlaybackState,
     ui_state::{waveform::WaveformState, UiState},
-    FFMPEG_AVAILABLE,
 };
 
 #[derive(Clone, Default, PartialEq, Eq)]
 pub enum ProgressDisplay {
     Waveform,
     Oscilloscope,
+    Spectrum,
+    Meter,
+    Vectorscope,
     #[default]
     ProgressBar,
 }
@@ -17,6 +19,9 @@ impl ProgressDisplay {
         match s {
             "oscilloscope" => Self::Oscilloscope,
             "waveform" => Self::Waveform,
+            "spectrum" => Self::Spectrum,
+            "meter" => Self::Meter,
+            "vectorscope" => Self::Vectorscope,
             _ => Self::ProgressBar,
         }
     }
@@ -28,6 +33,9 @@ impl std::fmt::Display for ProgressDisplay {
             ProgressDisplay::Waveform => write!(f, "waveform"),
             ProgressDisplay::ProgressBar => write!(f, "progress_bar"),
             ProgressDisplay::Oscilloscope => write!(f, "oscilloscope"),
+            ProgressDisplay::Spectrum => write!(f, "spectrum"),
+            ProgressDisplay::Meter => write!(f, "meter"),
+            ProgressDisplay::Vectorscope => write!(f, "vectorscope"),
         }
     }
 }
@@ -43,18 +51,23 @@ impl UiState {
 
     pub fn set_progress_display(&mut self, display: ProgressDisplay) {
         self.progress_display = match display {
-            ProgressDisplay::Waveform => {
-                if *FFMPEG_AVAILABLE {
-                    match self.get_waveform_state() {
-                        &WaveformState::Ready(_) => ProgressDisplay::Waveform,
-                        _ => ProgressDisplay::default(),
-                    }
-                } else {
-                    ProgressDisplay::default()
-                }
-            }
+            ProgressDisplay::Waveform => match self.get_waveform_state() {
+                &WaveformState::Ready(_) => ProgressDisplay::Waveform,
+                _ => ProgressDisplay::default(),
+            },
             ProgressDisplay::Oscilloscope => display,
+            ProgressDisplay::Spectrum => display,
+            ProgressDisplay::Meter => display,
+            ProgressDisplay::Vectorscope => display,
             ProgressDisplay::ProgressBar => display,
         }
     }
+
+    /// The meter and vectorscope share a key, which flips between the two
+    pub fn next_stereo_display(&self) -> ProgressDisplay {
+        match self.progress_display {
+            ProgressDisplay::Meter => ProgressDisplay::Vectorscope,
+            _ => ProgressDisplay::Meter,
+        }
+    }
 }
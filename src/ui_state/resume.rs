@@ -0,0 +1,49 @@
+use crate::{library::SimpleSong, ui_state::UiState};
+use anyhow::Result;
+use std::time::Duration;
+
+/// Positions this close to either end count as not started, or finished
+const RESUME_MARGIN: Duration = Duration::from_secs(15);
+
+impl UiState {
+    pub(crate) fn load_resume_positions(&mut self) -> Result<()> {
+        self.resume_positions = self.db_worker.get_resume_positions()?;
+        Ok(())
+    }
+
+    /// Where a long track was left off, if anywhere
+    pub fn get_resume_position(&self, song: &SimpleSong) -> Option<Duration> {
+        self.resume_positions.get(&song.id).copied()
+    }
+
+    /// How far through a partly listened track is, from 0.0 to 1.0
+    pub fn get_resume_progress(&self, song: &SimpleSong) -> Option<f32> {
+        let pos = self.get_resume_position(song)?;
+        Some((pos.as_secs_f32() / song.duration.as_secs_f32()).min(1.0))
+    }
+
+    pub(crate) fn save_resume_position(&mut self, song: &SimpleSong, pos: Duration) {
+        let Some(threshold) = self.resume_after else {
+            return;
+        };
+
+        if song.duration < threshold {
+            return;
+        }
+
+        let unfinished = song.duration.saturating_sub(RESUME_MARGIN);
+        match pos > RESUME_MARGIN && pos < unfinished {
+            true => {
+                self.resume_positions.insert(song.id, pos);
+                self.db_worker.set_resume_position(song.id, Some(pos));
+            }
+            false => self.clear_resume_position(song.id),
+        }
+    }
+
+    pub(crate) fn clear_resume_position(&mut self, id: u64) {
+        if self.resume_positions.remove(&id).is_some() {
+            self.db_worker.set_resume_position(id, None);
+        }
+    }
+}
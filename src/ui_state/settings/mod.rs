@@ -1,3 +1,4 @@
+mod output_device;
 mod root_mgmt;
 
 #[derive(Default, PartialEq, Clone)]
@@ -6,4 +7,5 @@ pub enum SettingsMode {
     ViewRoots,
     AddRoot,
     RemoveRoot,
+    OutputDevice,
 }
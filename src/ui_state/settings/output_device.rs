@@ -0,0 +1,40 @@
+use crate::{
+    player::output_devices,
+    ui_state::{PopupType, SettingsMode, UiState},
+};
+
+impl UiState {
+    /// Entries for the device list. The first is always the system default,
+    /// represented by `None`.
+    pub fn get_output_devices(&self) -> Vec<Option<&str>> {
+        std::iter::once(None)
+            .chain(self.output_devices.iter().map(|d| Some(d.as_str())))
+            .collect()
+    }
+
+    pub fn get_output_device(&self) -> Option<&str> {
+        self.output_device.as_deref()
+    }
+
+    pub(crate) fn selected_output_device(&self) -> Option<Option<String>> {
+        let idx = self.popup.selection.selected()?;
+        match idx {
+            0 => Some(None),
+            _ => self.output_devices.get(idx - 1).cloned().map(Some),
+        }
+    }
+
+    pub fn view_output_devices(&mut self) {
+        // Devices come and go, so the list is refreshed every time
+        self.output_devices = output_devices();
+
+        let current = self
+            .get_output_devices()
+            .iter()
+            .position(|d| *d == self.get_output_device())
+            .unwrap_or(0);
+
+        self.popup.selection.select(Some(current));
+        self.show_popup(PopupType::Settings(SettingsMode::OutputDevice));
+    }
+}
@@ -0,0 +1,70 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    playback::SleepTarget,
+    ui_state::{PopupType, UiState},
+};
+
+#[derive(Clone, Copy)]
+pub enum SleepChoice {
+    Minutes(u64),
+    EndOfTrack,
+    EndOfAlbum,
+}
+
+pub const SLEEP_CHOICES: [SleepChoice; 8] = [
+    SleepChoice::Minutes(10),
+    SleepChoice::Minutes(15),
+    SleepChoice::Minutes(30),
+    SleepChoice::Minutes(45),
+    SleepChoice::Minutes(60),
+    SleepChoice::Minutes(90),
+    SleepChoice::EndOfTrack,
+    SleepChoice::EndOfAlbum,
+];
+
+impl SleepChoice {
+    pub fn label(&self) -> String {
+        match self {
+            SleepChoice::Minutes(m) => format!("{m} minutes"),
+            SleepChoice::EndOfTrack => "End of track".to_string(),
+            SleepChoice::EndOfAlbum => "End of album".to_string(),
+        }
+    }
+
+    /// Timed choices count from the moment they are picked, paused or not,
+    /// so they are only accepted while something plays
+    pub fn target(&self) -> SleepTarget {
+        match self {
+            SleepChoice::Minutes(m) => {
+                SleepTarget::At(Instant::now() + Duration::from_secs(m * 60))
+            }
+            SleepChoice::EndOfTrack => SleepTarget::EndOfTrack,
+            SleepChoice::EndOfAlbum => SleepTarget::EndOfAlbum,
+        }
+    }
+}
+
+impl UiState {
+    pub fn open_sleep_timer(&mut self) {
+        self.popup.selection.select(Some(0));
+        self.show_popup(PopupType::SleepTimer);
+    }
+
+    pub fn get_sleep_choice(&self) -> Option<SleepChoice> {
+        let idx = self.popup.selection.selected()?;
+        SLEEP_CHOICES.get(idx).copied()
+    }
+
+    /// Applies to the running timer too, not just the next one
+    pub fn toggle_sleep_fade(&mut self) {
+        self.sleep_fade = !self.sleep_fade;
+        if let Some(timer) = self.playback.sleep_timer_mut() {
+            timer.fade = self.sleep_fade;
+        }
+    }
+
+    pub fn get_sleep_remaining(&self) -> Option<Duration> {
+        self.playback.sleep_remaining(self.get_playback_elapsed())
+    }
+}
@@ -0,0 +1,183 @@
+use crate::ui_state::UiState;
+use std::{
+    f32::consts::PI,
+    time::{Duration, Instant},
+};
+
+/// Frames per transform, about 46ms of audio at 44.1kHz
+pub const FFT_SIZE: usize = 2048;
+
+const MIN_FREQ: f32 = 40.0;
+const MAX_FREQ: f32 = 16_000.0;
+/// Bars cover this many decibels below full scale
+const DB_RANGE: f32 = 60.0;
+
+/// Full heights per second a bar sinks once the level drops
+const BAR_FALL: f32 = 2.5;
+/// Peaks stay put this long before they start sinking
+const PEAK_HOLD: Duration = Duration::from_millis(600);
+const PEAK_FALL: f32 = 0.6;
+
+pub struct SpectrumState {
+    bars: Vec<f32>,
+    peaks: Vec<Peak>,
+    window: Vec<f32>,
+    updated: Instant,
+}
+
+#[derive(Clone, Copy)]
+pub struct Peak {
+    pub level: f32,
+    set_at: Instant,
+}
+
+impl Default for SpectrumState {
+    fn default() -> Self {
+        SpectrumState {
+            bars: Vec::new(),
+            peaks: Vec::new(),
+            // Hann window, tames the leakage between neighbouring bins
+            window: (0..FFT_SIZE)
+                .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / FFT_SIZE as f32).cos())
+                .collect(),
+            updated: Instant::now(),
+        }
+    }
+}
+
+impl Peak {
+    fn new(level: f32, set_at: Instant) -> Self {
+        Peak { level, set_at }
+    }
+}
+
+impl SpectrumState {
+    pub fn bars(&self) -> &[f32] {
+        &self.bars
+    }
+
+    pub fn peaks(&self) -> &[Peak] {
+        &self.peaks
+    }
+
+    /// Runs the transform over `samples` (mono, newest last) and folds the
+    /// result into `bands` log spaced bars. Bars rise instantly but fall
+    /// gradually, and peaks hold for a moment above them.
+    fn update(&mut self, samples: &[f32], sample_rate: u32, bands: usize) {
+        let now = Instant::now();
+        let dt = now.duration_since(self.updated).as_secs_f32();
+        self.updated = now;
+
+        if self.bars.len() != bands {
+            self.bars = vec![0.0; bands];
+            self.peaks = vec![Peak::new(0.0, now); bands];
+        }
+
+        let levels = band_levels(&self.window, samples, sample_rate, bands);
+
+        for ((bar, peak), level) in self.bars.iter_mut().zip(&mut self.peaks).zip(levels) {
+            *bar = level.max(*bar - BAR_FALL * dt);
+
+            if *bar >= peak.level {
+                *peak = Peak::new(*bar, now);
+            } else if now.duration_since(peak.set_at) > PEAK_HOLD {
+                peak.level = (peak.level - PEAK_FALL * dt).max(*bar);
+            }
+        }
+    }
+}
+
+impl UiState {
+    pub fn update_spectrum(&mut self, bands: usize) {
+        let window = self.metrics.latest_frames(FFT_SIZE);
+        let samples = window.mono();
+
+        self.spectrum.update(&samples, window.sample_rate, bands);
+    }
+
+    pub fn get_spectrum(&self) -> &SpectrumState {
+        &self.spectrum
+    }
+}
+
+/// Loudest bin in each band, scaled from `-DB_RANGE..0` dBFS to `0..1`
+fn band_levels(window: &[f32], samples: &[f32], sample_rate: u32, bands: usize) -> Vec<f32> {
+    // Short reads (right after a track starts) are padded at the front
+    let offset = FFT_SIZE.saturating_sub(samples.len());
+    let samples = &samples[samples.len().saturating_sub(FFT_SIZE)..];
+
+    let mut re = vec![0.0; FFT_SIZE];
+    let mut im = vec![0.0; FFT_SIZE];
+    for (idx, sample) in samples.iter().enumerate() {
+        re[offset + idx] = sample * window[offset + idx];
+    }
+
+    fft(&mut re, &mut im);
+
+    // The Hann window halves the amplitude of a full scale sine
+    let scale = 4.0 / FFT_SIZE as f32;
+    let bin_hz = sample_rate.max(1) as f32 / FFT_SIZE as f32;
+    let max_freq = MAX_FREQ.min(sample_rate as f32 / 2.0);
+    let ratio = (max_freq / MIN_FREQ).max(1.0);
+
+    (0..bands)
+        .map(|band| {
+            let lo = MIN_FREQ * ratio.powf(band as f32 / bands as f32);
+            let hi = MIN_FREQ * ratio.powf((band + 1) as f32 / bands as f32);
+
+            let first = ((lo / bin_hz) as usize).clamp(1, FFT_SIZE / 2 - 1);
+            let last = ((hi / bin_hz).ceil() as usize).clamp(first + 1, FFT_SIZE / 2);
+
+            let magnitude = (first..last)
+                .map(|bin| (re[bin] * re[bin] + im[bin] * im[bin]).sqrt() * scale)
+                .fold(0.0, f32::max);
+
+            let db = 20.0 * magnitude.max(f32::EPSILON).log10();
+            ((db + DB_RANGE) / DB_RANGE).clamp(0.0, 1.0)
+        })
+        .collect()
+}
+
+/// In place radix-2 transform, `re` and `im` must be a power of two long
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let (w_im, w_re) = (-2.0 * PI / len as f32).sin_cos();
+
+        for start in (0..n).step_by(len) {
+            let (mut cur_re, mut cur_im) = (1.0, 0.0);
+
+            for k in 0..len / 2 {
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * cur_re - im[b] * cur_im;
+                let t_im = re[b] * cur_im + im[b] * cur_re;
+
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+
+                (cur_re, cur_im) = (cur_re * w_re - cur_im * w_im, cur_re * w_im + cur_im * w_re);
+            }
+        }
+
+        len <<= 1;
+    }
+}
@@ -48,6 +48,14 @@ impl UiState {
 }
 
 impl DisplayTheme {
+    /// Gradients scroll along with playback, so they need every frame drawn
+    pub fn is_animated(&self) -> bool {
+        let scrolls = matches!(self.progress_played, ProgressGradient::Gradient(_))
+            || matches!(self.progress_unplayed, InactiveGradient::Gradient(_));
+
+        scrolls && self.progress_speed != 0.0
+    }
+
     pub fn get_focused_color(&self, position: f32, time: f32) -> Color {
         match &self.progress_played {
             ProgressGradient::Static(c) => *c,
@@ -1,6 +1,6 @@
 use anyhow::Result;
 
-use crate::ui_state::ProgressDisplay;
+use crate::{playback::RepeatMode, ui_state::ProgressDisplay};
 
 use super::{AlbumSort, Mode, Pane, UiState};
 
@@ -23,6 +23,16 @@ pub struct UiSnapshot {
 
     pub progress_display: String,
     pub smoothing_factor: f32,
+
+    pub volume: Option<u8>,
+    pub muted: bool,
+
+    pub eq_preset: String,
+    pub eq_gains: Vec<f32>,
+
+    pub output_device: Option<String>,
+
+    pub repeat: String,
 }
 
 impl UiSnapshot {
@@ -35,8 +45,30 @@ impl UiSnapshot {
             ("ui_smooth", format!("{:.1}", self.smoothing_factor)),
             ("ui_sidebar_percent", self.sidebar_percentage.to_string()),
             ("ui_progress_display", self.progress_display.to_string()),
+            ("ui_muted", self.muted.to_string()),
+            ("ui_eq_preset", self.eq_preset.clone()),
+            // Empty for the system default
+            (
+                "ui_output_device",
+                self.output_device.clone().unwrap_or_default(),
+            ),
+            ("ui_repeat", self.repeat.clone()),
         ];
 
+        if !self.eq_gains.is_empty() {
+            let gains = self
+                .eq_gains
+                .iter()
+                .map(|g| format!("{g:.1}"))
+                .collect::<Vec<_>>()
+                .join(",");
+            pairs.push(("ui_eq_gains", gains));
+        }
+
+        if let Some(volume) = self.volume {
+            pairs.push(("ui_volume", volume.to_string()));
+        }
+
         if let Some(pos) = self.album_selection {
             pairs.push(("ui_album_pos", pos.to_string()));
             pairs.push(("ui_album_offset", self.album_sel_offset.to_string()))
@@ -71,6 +103,16 @@ impl UiSnapshot {
                 "ui_playlist_offset" => snapshot.playlist_sel_offset = value.parse().unwrap_or(0),
                 "ui_song_pos" => snapshot.song_selection = value.parse().ok(),
                 "ui_song_offset" => snapshot.song_sel_offset = value.parse::<usize>().unwrap_or(0),
+                "ui_volume" => snapshot.volume = value.parse().ok(),
+                "ui_muted" => snapshot.muted = value.parse().unwrap_or(false),
+                "ui_eq_preset" => snapshot.eq_preset = value,
+                "ui_repeat" => snapshot.repeat = value,
+                "ui_output_device" => {
+                    snapshot.output_device = Some(value).filter(|d| !d.is_empty())
+                }
+                "ui_eq_gains" => {
+                    snapshot.eq_gains = value.split(',').filter_map(|g| g.parse().ok()).collect()
+                }
                 "ui_smooth" => snapshot.smoothing_factor = value.parse::<f32>().unwrap_or(1.0),
                 "ui_sidebar_percent" => {
                     snapshot.sidebar_percentage = value.parse::<u16>().unwrap_or(30)
@@ -109,6 +151,16 @@ impl UiState {
 
             progress_display: self.get_progress_display().to_string(),
             smoothing_factor: self.get_smoothing_factor(),
+
+            volume: Some(self.get_volume()),
+            muted: self.is_muted(),
+
+            eq_preset: self.equalizer.active_name().to_string(),
+            eq_gains: self.equalizer.settings().gains.to_vec(),
+
+            output_device: self.output_device.clone(),
+
+            repeat: self.playback.repeat().to_string(),
         }
     }
 
@@ -118,6 +170,27 @@ impl UiState {
         Ok(())
     }
 
+    /// Queue, current track and position, picked back up on the next launch
+    pub fn save_session(&mut self) -> Result<()> {
+        let queue = self.playback.get_queue().iter().map(|s| s.id).collect();
+        let now_playing = self
+            .get_now_playing()
+            .filter(|_| self.player_is_active())
+            .map(|s| s.id);
+
+        self.db_worker
+            .save_session(queue, now_playing, self.get_playback_elapsed())
+    }
+
+    /// Volume is owned by the player thread, so it's handed back to the
+    /// caller rather than applied here
+    pub fn saved_volume(&self) -> Result<Option<(u8, bool)>> {
+        Ok(self
+            .db_worker
+            .load_ui_snapshot()?
+            .and_then(|s| s.volume.map(|v| (v, s.muted))))
+    }
+
     pub fn restore_state(&mut self) -> Result<()> {
         // The order of these function calls is particularly important
         if let Some(snapshot) = self.db_worker.load_ui_snapshot()? {
@@ -165,6 +238,14 @@ impl UiState {
 
             self.display_state.sidebar_percent = snapshot.sidebar_percentage;
 
+            self.equalizer
+                .restore(&snapshot.eq_preset, &snapshot.eq_gains);
+
+            self.output_device = snapshot.output_device;
+
+            self.playback
+                .set_repeat(RepeatMode::from_str(&snapshot.repeat));
+
             if let Some(pos) = snapshot.song_selection {
                 if pos < self.legal_songs.len() {
                     self.display_state.table_pos.select(Some(pos));
@@ -1,18 +1,18 @@
 use super::{DisplayState, search_state::SearchState};
 use crate::{
-    Library, PlaybackSession,
+    Library, PlaybackSession, REFRESH_RATE, SLOW_REFRESH_RATE,
     database::DbWorker,
     key_handler::InputContext,
-    library::SimpleSong,
-    player::{PlaybackMetrics, PlaybackState},
+    library::{SimpleSong, SongInfo},
+    player::{OSCILLO_BUFFER_CAPACITY, PlaybackMetrics, PlaybackState, TapWindow},
     ui_state::{
-        LibraryView, Mode, Pane, PlaylistAction, ProgressDisplay, SettingsMode, ThemeManager,
-        UiState, WaveformManager,
+        EqManager, LibraryView, Mode, Pane, PlaylistAction, ProgressDisplay, SettingsMode,
+        SpectrumState, ThemeManager, UiState, WaveformManager,
         popup::{PopupState, PopupType},
     },
 };
 use anyhow::{Error, Result};
-use std::{sync::Arc, time::Duration};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 impl UiState {
     pub fn new(library: Arc<Library>, metrics: Arc<PlaybackMetrics>) -> Self {
@@ -27,15 +27,27 @@ impl UiState {
 
             waveform: WaveformManager::new(),
             progress_display: ProgressDisplay::Oscilloscope,
+            spectrum: SpectrumState::default(),
 
             popup: PopupState::new(),
             theme_manager: ThemeManager::new(),
+            equalizer: EqManager::new(),
             albums: Vec::new(),
             legal_songs: Vec::new(),
             playlists: Vec::new(),
 
+            output_devices: Vec::new(),
+            output_device: None,
+            sleep_fade: true,
+
+            resume_after: None,
+            resume_positions: HashMap::new(),
+
+            chapters: Vec::new(),
+
             library_refresh_progress: None,
             library_refresh_detail: None,
+            waveform_batch: None,
         }
     }
 }
@@ -118,6 +130,7 @@ impl UiState {
                     PopupType::Playlist(PlaylistAction::CreateWithSongs)
                 )
                 | (Pane::Popup, PopupType::Playlist(PlaylistAction::Rename))
+                | (Pane::Popup, PopupType::SeekTo)
         )
     }
 }
@@ -145,12 +158,22 @@ impl UiState {
 }
 
 impl UiState {
-    pub fn get_tapped_samples(&self) -> Vec<f32> {
-        self.metrics.drain_samples()
+    pub fn get_tapped_samples(&self) -> TapWindow {
+        self.metrics.latest_frames(OSCILLO_BUFFER_CAPACITY)
     }
 
     pub fn peek_queue(&self) -> Option<&Arc<SimpleSong>> {
-        self.playback.peek_queue()
+        self.playback.up_next()
+    }
+
+    /// Songs for repeat-context to loop when playback starts from the current
+    /// view. Playing from the queue keeps whatever context came before.
+    pub(crate) fn get_repeat_context(&self) -> Option<Vec<Arc<SimpleSong>>> {
+        match self.get_mode() {
+            Mode::Library(_) => Some(self.legal_songs.clone()),
+            Mode::Power | Mode::Search => Some(Vec::new()),
+            _ => None,
+        }
     }
 
     pub fn queue_is_empty(&self) -> bool {
@@ -161,6 +184,18 @@ impl UiState {
         self.metrics.is_paused()
     }
 
+    pub fn get_volume(&self) -> u8 {
+        self.metrics.get_volume()
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.metrics.is_muted()
+    }
+
+    pub fn get_playback_speed(&self) -> f32 {
+        self.metrics.get_speed()
+    }
+
     pub fn set_now_playing(&mut self, song: Option<Arc<SimpleSong>>) {
         self.playback.set_now_playing(song);
     }
@@ -177,7 +212,43 @@ impl UiState {
         self.metrics.get_elapsed().as_secs_f32()
     }
 
+    /// A-B loop points as fractions of the current track, B only once set
+    pub fn get_loop_ratios(&self) -> Option<(f32, Option<f32>)> {
+        let ab_loop = self.playback.ab_loop()?;
+        let duration = self.get_now_playing()?.get_duration().as_secs_f32();
+        let ratio = |pos: Duration| (pos.as_secs_f32() / duration).min(1.0);
+
+        Some((ratio(ab_loop.a), ab_loop.b.map(ratio)))
+    }
+
     pub fn player_is_active(&self) -> bool {
         self.metrics.get_state() != PlaybackState::Stopped && self.get_now_playing().is_some()
     }
+
+    /// How long the screen can go without a redraw when nothing else
+    /// happens. Only gradients and the oscilloscope need every frame, and a
+    /// paused player without a sleep timer needs none at all.
+    pub fn next_frame(&self) -> Option<Duration> {
+        if !self.player_is_active() {
+            return None;
+        }
+
+        let playing = !self.metrics.is_paused();
+        let animated = match self.get_progress_display() {
+            ProgressDisplay::Oscilloscope
+            | ProgressDisplay::Spectrum
+            | ProgressDisplay::Meter
+            | ProgressDisplay::Vectorscope => true,
+            ProgressDisplay::Waveform => !self.has_waveform(),
+            ProgressDisplay::ProgressBar => false,
+        } || self.theme_manager.get_display_theme(true).is_animated();
+
+        if playing && animated && self.display_state.progress_visible {
+            Some(REFRESH_RATE)
+        } else if playing || self.get_sleep_remaining().is_some() {
+            Some(SLOW_REFRESH_RATE)
+        } else {
+            None
+        }
+    }
 }
@@ -1,54 +1,88 @@
 use anyhow::{Context, Result, anyhow, bail};
 use crossbeam_channel::Receiver;
-use std::{
-    io::{Cursor, Read},
-    path::Path,
-    process::Command,
-    sync::Arc,
-    thread,
-    time::Duration,
+use std::{fs::File, io::ErrorKind, path::Path, process::Command, sync::Arc, thread};
+use symphonia::{
+    core::{
+        audio::SampleBuffer,
+        codecs::{CODEC_TYPE_NULL, DecoderOptions},
+        errors::Error as SymphoniaError,
+        formats::FormatOptions,
+        io::MediaSourceStream,
+        meta::MetadataOptions,
+        probe::Hint,
+    },
+    default::{get_codecs, get_probe},
 };
 
 use crate::{
+    FFMPEG_AVAILABLE,
     key_handler::Incrementor,
-    library::{SimpleSong, SongDatabase},
+    library::{SimpleSong, SongDatabase, SongInfo, WaveformPeaks},
     ui_state::UiState,
 };
 
+/// Fewest points drawn across the view, the level used is picked to match
 const WF_LEN: usize = 500;
 static WAVEFORM_STEP: f32 = 0.5;
-const MIN_SAMPLES_PER_POINT: usize = 200; // Minimum for short files
-const MAX_SAMPLES_PER_POINT: usize = 4000; // Maximum for very long files
+/// Zooming stops once the view would hold fewer points than this
+const MIN_ZOOMED_POINTS: usize = 128;
+/// Share of the view moved by a single pan
+const PAN_STEP: f32 = 0.25;
+
+/// Decoded audio is brought down to about this rate before the envelope is taken
+const PCM_RATE: u32 = 22050;
 
 #[derive(PartialEq)]
 pub enum WaveformState {
     None,
     Loading,
-    Ready(Vec<f32>),
+    Ready(WaveformPeaks),
     Failed,
 }
 
+/// Progress of the batch filling in waveforms for the whole library
+#[derive(Clone, Copy)]
+pub struct WaveformBatchStatus {
+    pub current: usize,
+    pub total: usize,
+    pub paused: bool,
+}
+
+/// The stretch of the track currently in view. `start`, `end` and the
+/// playhead's `progress` are all fractions of the whole track.
+pub struct WaveformView {
+    pub points: Vec<(f32, f32)>,
+    pub start: f32,
+    pub end: f32,
+    pub progress: f32,
+}
+
 pub struct WaveformManager {
     state: WaveformState,
-    smoothed_view: Vec<f32>,
     smoothing_factor: f32,
-    reciever: Option<Receiver<Result<Vec<f32>>>>,
+    // Fullscreen only. The view covers `1 / zoom` of the track, centred on
+    // the playhead and shifted by `pan` (also a fraction of the track).
+    zoom: u32,
+    pan: f32,
+    reciever: Option<Receiver<Result<WaveformPeaks>>>,
 }
 
 impl WaveformManager {
     pub fn new() -> Self {
         WaveformManager {
             state: WaveformState::None,
-            smoothed_view: Vec::with_capacity(WF_LEN),
             smoothing_factor: 1.0,
+            zoom: 1,
+            pan: 0.0,
             reciever: None,
         }
     }
 
     pub fn request(&mut self, song: &SimpleSong) {
+        self.pan = 0.0;
+
         if let Ok(cached) = song.get_waveform() {
             self.state = WaveformState::Ready(cached);
-            self.apply_smoothing();
             return;
         }
 
@@ -65,18 +99,17 @@ impl WaveformManager {
         }
     }
 
-    pub fn reciever(&self) -> Option<&Receiver<Result<Vec<f32>>>> {
+    pub fn reciever(&self) -> Option<&Receiver<Result<WaveformPeaks>>> {
         self.reciever.as_ref()
     }
 
-    pub fn complete(&mut self, result: Result<Vec<f32>>, song: Option<&Arc<SimpleSong>>) {
+    pub fn complete(&mut self, result: Result<WaveformPeaks>, song: Option<&Arc<SimpleSong>>) {
         match result {
             Ok(waveform) => {
                 if let Some(s) = song {
                     let _ = s.set_waveform_db(&waveform);
                 }
                 self.state = WaveformState::Ready(waveform);
-                self.apply_smoothing();
             }
             Err(_) => self.state = WaveformState::Failed,
         }
@@ -87,35 +120,86 @@ impl WaveformManager {
 impl WaveformManager {
     pub fn clear(&mut self) {
         self.reciever = None;
-        self.smoothed_view.clear();
+        self.pan = 0.0;
         self.state = WaveformState::None;
     }
 
-    pub fn apply_smoothing(&mut self) {
-        if let WaveformState::Ready(raw) = &mut self.state {
-            self.smoothed_view = smooth_waveform(raw, self.smoothing_factor);
-        }
-    }
-
     pub fn increment_smoothness(&mut self, direction: Incrementor) {
         match direction {
             Incrementor::Up => {
                 if self.smoothing_factor < 3.9 {
                     self.smoothing_factor += WAVEFORM_STEP;
-                    self.apply_smoothing();
                 }
             }
             Incrementor::Down => {
                 if self.smoothing_factor > 0.1 {
                     self.smoothing_factor -= WAVEFORM_STEP;
-                    self.apply_smoothing();
                 }
             }
         }
     }
 
-    fn get_waveform_visual(&self) -> &[f32] {
-        self.smoothed_view.as_slice()
+    /// Zooming in doubles the detail, up until the finest stored level
+    /// would be spread too thin
+    pub fn zoom(&mut self, direction: Incrementor) {
+        let max_zoom = match &self.state {
+            WaveformState::Ready(peaks) => (peaks.resolution() / MIN_ZOOMED_POINTS).max(1),
+            _ => return,
+        };
+
+        match direction {
+            Incrementor::Up if self.zoom * 2 <= max_zoom as u32 => self.zoom *= 2,
+            Incrementor::Down if self.zoom > 1 => self.zoom /= 2,
+            _ => (),
+        }
+
+        if self.zoom == 1 {
+            self.pan = 0.0;
+        }
+    }
+
+    pub fn pan(&mut self, direction: Incrementor) {
+        if self.zoom == 1 {
+            return;
+        }
+
+        let step = PAN_STEP / self.zoom as f32;
+        self.pan = match direction {
+            Incrementor::Up => self.pan + step,
+            Incrementor::Down => self.pan - step,
+        }
+        .clamp(-1.0, 1.0);
+    }
+
+    fn view(&self, progress: f32, zoomed: bool) -> Option<WaveformView> {
+        let WaveformState::Ready(peaks) = &self.state else {
+            return None;
+        };
+
+        let span = match zoomed {
+            true => 1.0 / self.zoom as f32,
+            false => 1.0,
+        };
+
+        // Kept inside the track, so the playhead drifts off centre towards
+        // either end of it
+        let start = (progress + self.pan - span / 2.0).clamp(0.0, 1.0 - span);
+        let end = start + span;
+
+        let (mins, maxes): (Vec<f32>, Vec<f32>) =
+            peaks.window(start, end, WF_LEN).iter().copied().unzip();
+
+        let points = smooth_waveform(&mins, self.smoothing_factor)
+            .into_iter()
+            .zip(smooth_waveform(&maxes, self.smoothing_factor))
+            .collect();
+
+        Some(WaveformView {
+            points,
+            start,
+            end,
+            progress,
+        })
     }
 }
 
@@ -124,7 +208,11 @@ impl UiState {
         self.waveform.request(song);
     }
 
-    pub fn handle_wf_result(&mut self, result: Result<Vec<f32>>, song: Option<&Arc<SimpleSong>>) {
+    pub fn handle_wf_result(
+        &mut self,
+        result: Result<WaveformPeaks>,
+        song: Option<&Arc<SimpleSong>>,
+    ) {
         self.waveform.complete(result, song);
     }
 
@@ -132,7 +220,7 @@ impl UiState {
         &self.waveform.state
     }
 
-    pub fn wf_reciever(&self) -> Option<&Receiver<Result<Vec<f32>>>> {
+    pub fn wf_reciever(&self) -> Option<&Receiver<Result<WaveformPeaks>>> {
         self.waveform.reciever()
     }
 
@@ -140,8 +228,37 @@ impl UiState {
         self.waveform.clear();
     }
 
-    pub fn get_waveform_as_slice(&self) -> &[f32] {
-        self.waveform.get_waveform_visual()
+    pub fn has_waveform(&self) -> bool {
+        matches!(self.waveform.state, WaveformState::Ready(_))
+    }
+
+    /// The whole track, or with `zoomed` the stretch around the playhead
+    /// picked out by the zoom & pan
+    pub fn get_waveform_view(&self, zoomed: bool) -> Option<WaveformView> {
+        let duration = self.get_now_playing()?.get_duration_f32();
+        let progress = match duration > 0.0 {
+            true => self.get_playback_elapsed_f32() / duration,
+            false => 0.0,
+        };
+
+        self.waveform.view(progress, zoomed)
+    }
+
+    pub fn get_waveform_zoom(&self) -> u32 {
+        self.waveform.zoom
+    }
+
+    pub fn zoom_waveform(&mut self, direction: Incrementor) {
+        self.waveform.zoom(direction);
+    }
+
+    pub fn pan_waveform(&mut self, direction: Incrementor) {
+        self.waveform.pan(direction);
+    }
+
+    /// Snaps the view back onto the playhead
+    pub fn center_waveform(&mut self) {
+        self.waveform.pan = 0.0;
     }
 
     pub fn get_smoothing_factor(&self) -> f32 {
@@ -155,70 +272,142 @@ impl UiState {
     pub fn increment_wf_smoothness(&mut self, direction: Incrementor) {
         self.waveform.increment_smoothness(direction);
     }
+
+    pub fn set_waveform_batch(&mut self, status: Option<WaveformBatchStatus>) {
+        self.waveform_batch = status;
+    }
+
+    pub fn get_waveform_batch(&self) -> Option<WaveformBatchStatus> {
+        self.waveform_batch
+    }
 }
 
-/// Generate a waveform using ffmpeg by piping output directly to memory
-pub fn generate_waveform<P: AsRef<Path>>(audio_path: P) -> Result<Vec<f32>> {
+/// Generate a waveform, decoding the file in-process and falling back to
+/// ffmpeg for anything symphonia can't read
+pub fn generate_waveform<P: AsRef<Path>>(audio_path: P) -> Result<WaveformPeaks> {
     let path = audio_path.as_ref();
     extract_waveform_data(path)
 }
 
-/// Extract duration from audio file using ffmpeg
-fn get_audio_duration<P: AsRef<Path>>(audio_path: P) -> Result<Duration> {
-    let audio_path_str = audio_path
-        .as_ref()
-        .to_str()
-        .ok_or_else(|| anyhow!("Audio path contains invalid Unicode"))?;
+/// Extract waveform data from audio file
+fn extract_waveform_data(audio_path: &Path) -> Result<WaveformPeaks> {
+    let waveform = match decode_ranges(audio_path) {
+        Ok((ranges, _)) if ranges.is_empty() => None,
+        Ok((ranges, rate)) => Some(WaveformPeaks::from_ranges(&ranges, rate)),
+        Err(e) => match *FFMPEG_AVAILABLE {
+            // The ffmpeg filter chain is kept for the look it has always had
+            true => {
+                let pcm = decode_pcm_ffmpeg(audio_path)?;
+                (!pcm.is_empty()).then(|| WaveformPeaks::from_samples(&pcm, PCM_RATE))
+            }
+            false => return Err(e),
+        },
+    };
 
-    // Use ffprobe to get duration
-    let output = Command::new("ffprobe")
-        .args(&[
-            "-v",
-            "error",
-            "-show_entries",
-            "format=duration",
-            "-of",
-            "default=noprint_wrappers=1:nokey=1",
-            audio_path_str,
-        ])
-        .output()
-        .context("Failed to execute ffprobe")?;
+    waveform.ok_or_else(|| anyhow!("Could not determine audio length"))
+}
 
-    if !output.status.success() {
-        bail!(
-            "ffprobe failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        );
+/// Decode the file with symphonia into the min & max of every run of
+/// frames, sized to land somewhere near `PCM_RATE`. Taken straight from the
+/// source samples across all channels, so no peak is lost to mixing or
+/// filtering, and long files don't eat memory.
+fn decode_ranges(path: &Path) -> Result<(Vec<(f32, f32)>, u32)> {
+    let file = File::open(path)?;
+    let mss = MediaSourceStream::new(Box::new(file), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(ext);
     }
 
-    let duration_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    let duration_secs = duration_str
-        .parse::<f64>()
-        .context("Failed to parse duration")?;
+    let mut format = get_probe()
+        .format(
+            &hint,
+            mss,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )?
+        .format;
+
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| anyhow!("No playable audio track in {}", path.display()))?;
+
+    let params = track.codec_params.clone();
+    let track_id = track.id;
 
-    Ok(Duration::from_secs_f64(duration_secs))
-}
+    let source_rate = params
+        .sample_rate
+        .ok_or_else(|| anyhow!("Unknown sample rate for {}", path.display()))?;
 
-/// Extract waveform data from audio file
-fn extract_waveform_data<P: AsRef<Path>>(audio_path: P) -> Result<Vec<f32>> {
-    // Get audio duration to calculate optimal sampling
-    let duration = match get_audio_duration(&audio_path) {
-        Ok(d) => d,
-        Err(_) => {
-            bail!("Could not determine audio length");
+    let mut decoder = get_codecs().make(&params, &DecoderOptions::default())?;
+
+    let factor = (source_rate / PCM_RATE).max(1) as usize;
+    let mut ranges = Vec::with_capacity(params.n_frames.map_or(0, |n| n as usize / factor));
+    let mut sample_buf: Option<SampleBuffer<f32>> = None;
+    let (mut range, mut counted) = ((f32::MAX, f32::MIN), 0);
+
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(e)) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        };
+
+        if packet.track_id() != track_id {
+            continue;
         }
-    };
 
-    // Calculate adaptive samples per point based on duration
-    let samples_per_point = calculate_adaptive_samples(duration);
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // A corrupt packet leaves a gap, not a failed waveform
+            Err(SymphoniaError::DecodeError(_)) => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        let spec = *decoded.spec();
+        let channels = spec.channels.count();
+
+        if decoded.frames() == 0 {
+            continue;
+        }
 
+        let needed = decoded.capacity() * channels;
+        if sample_buf.as_ref().is_some_and(|b| b.capacity() < needed) {
+            sample_buf = None;
+        }
+
+        let buf =
+            sample_buf.get_or_insert_with(|| SampleBuffer::new(decoded.capacity() as u64, spec));
+        buf.copy_interleaved_ref(decoded);
+
+        for frame in buf.samples().chunks_exact(channels) {
+            for &sample in frame {
+                range = (range.0.min(sample), range.1.max(sample));
+            }
+            counted += 1;
+
+            if counted == factor {
+                ranges.push(range);
+                (range, counted) = ((f32::MAX, f32::MIN), 0);
+            }
+        }
+    }
+
+    Ok((ranges, source_rate / factor as u32))
+}
+
+/// Pipe the file through ffmpeg, which hands back mono samples at `PCM_RATE`
+fn decode_pcm_ffmpeg(audio_path: &Path) -> Result<Vec<f32>> {
     // Get the path as string, with better error handling
     let audio_path_str = audio_path
-        .as_ref()
         .to_str()
         .ok_or_else(|| anyhow!("Audio path contains invalid Unicode"))?;
 
     // Create a process to pipe audio data directly to memory using ffmpeg
+    let rate = PCM_RATE.to_string();
     let mut cmd = Command::new("ffmpeg");
     let output = cmd
         .args(&[
@@ -227,7 +416,7 @@ fn extract_waveform_data<P: AsRef<Path>>(audio_path: P) -> Result<Vec<f32>> {
             "-ac",
             "1", // Convert to mono
             "-ar",
-            "22050",
+            &rate,
             "-af",
             "dynaudnorm=f=500:g=31,highpass=f=350,volume=2,bass=gain=-8:frequency=200,treble=gain=10:frequency=6000", // I wish I could explain this, but this is the best we're gonna get without having a masters in audio engineering
             "-loglevel",
@@ -247,157 +436,11 @@ fn extract_waveform_data<P: AsRef<Path>>(audio_path: P) -> Result<Vec<f32>> {
         );
     }
 
-    let pcm_data = output.stdout;
-    let mut waveform = process_pcm_to_waveform(&pcm_data, samples_per_point)?;
-
-    normalize_waveform(&mut waveform);
-
-    Ok(waveform)
-}
-
-/// Calculate adaptive samples per point based on duration
-fn calculate_adaptive_samples(duration: Duration) -> usize {
-    let duration_secs = duration.as_secs_f32();
-    let sample_rate = 44100.0; // Standard sample rate
-
-    let total_samples = (duration_secs * sample_rate) as usize;
-    let ideal_samples = total_samples / (WF_LEN * 10);
-
-    ideal_samples.clamp(MIN_SAMPLES_PER_POINT, MAX_SAMPLES_PER_POINT)
-}
-
-/// Process raw PCM float data into a vector of f32 values
-fn process_pcm_to_waveform(pcm_data: &[u8], samples_per_point: usize) -> Result<Vec<f32>> {
-    let mut cursor = Cursor::new(pcm_data);
-
-    let total_samples = pcm_data.len() / 4;
-
-    // If the file is very short, adapt the approach
-    if total_samples < WF_LEN * samples_per_point {
-        return process_short_pcm(pcm_data);
-    }
-
-    let sample_step = total_samples / WF_LEN;
-    let mut waveform = Vec::with_capacity(WF_LEN);
-
-    for i in 0..WF_LEN {
-        let position = i * sample_step * 4;
-        if position >= pcm_data.len() {
-            break;
-        }
-
-        cursor.set_position(position as u64);
-        let mut sum_squares = 0.0;
-        let mut samples_read = 0;
-        let mut max_value = 0.0f32;
-
-        let max_samples = samples_per_point.min(sample_step);
-        for _ in 0..max_samples {
-            if cursor.position() >= pcm_data.len() as u64 {
-                break;
-            }
-
-            let mut bytes = [0u8; 4];
-            match cursor.read_exact(&mut bytes) {
-                Ok(_) => {
-                    let sample = f32::from_le_bytes(bytes);
-                    let abs_sample = sample.abs();
-                    if abs_sample > max_value {
-                        max_value = abs_sample;
-                    }
-
-                    // Sum squares for RMS calculation
-                    sum_squares += sample * sample;
-                    samples_read += 1;
-                }
-                Err(_) => break,
-            }
-        }
-
-        match samples_read > 0 {
-            true => {
-                let rms = (sum_squares / samples_read as f32).sqrt();
-                let value = rms.min(1.0);
-                waveform.push(value);
-            }
-            false => waveform.push(0.0),
-        }
-    }
-
-    while waveform.len() < WF_LEN {
-        waveform.push(0.0);
-    }
-
-    Ok(waveform)
-}
-
-/// Process very short PCM files
-fn process_short_pcm(pcm_data: &[u8]) -> Result<Vec<f32>> {
-    let mut cursor = Cursor::new(pcm_data);
-    let total_samples = pcm_data.len() / 4;
-
-    // For very short files, we'll divide the available samples evenly
-    let samples_per_section = total_samples / WF_LEN.max(1);
-    let extra_samples = total_samples % WF_LEN;
-
-    let mut waveform = Vec::with_capacity(WF_LEN);
-    let mut position = 0;
-
-    for i in 0..WF_LEN {
-        let samples_this_section = if i < extra_samples {
-            samples_per_section + 1
-        } else {
-            samples_per_section
-        };
-
-        if samples_this_section == 0 {
-            waveform.push(0.0);
-            continue;
-        }
-
-        cursor.set_position((position * 4) as u64);
-
-        let mut sum_squares = 0.0;
-        let mut max_value = 0.0f32;
-        let mut samples_read = 0;
-
-        for _ in 0..samples_this_section {
-            if cursor.position() >= pcm_data.len() as u64 {
-                break;
-            }
-
-            let mut bytes = [0u8; 4];
-            match cursor.read_exact(&mut bytes) {
-                Ok(_) => {
-                    let sample = f32::from_le_bytes(bytes);
-                    let abs_sample = sample.abs();
-                    if abs_sample > max_value {
-                        max_value = abs_sample;
-                    }
-                    sum_squares += sample * sample;
-                    samples_read += 1;
-                }
-                Err(_) => break,
-            }
-        }
-
-        position += samples_this_section;
-
-        match samples_read > 0 {
-            true => {
-                let rms = (sum_squares / samples_read as f32).sqrt();
-                let value = rms.min(1.0);
-                waveform.push(value);
-            }
-            false => waveform.push(0.0),
-        }
-    }
-
-    while waveform.len() < WF_LEN {
-        waveform.push(0.0);
-    }
-
-    Ok(waveform)
+    Ok(output
+        .stdout
+        .chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect())
 }
 
 /// Apply a smoothing filter to the waveform with float smoothing factor
@@ -445,27 +488,3 @@ pub fn smooth_waveform(waveform: &[f32], smoothing_factor: f32) -> Vec<f32> {
         })
         .collect()
 }
-
-/// Normalize the waveform to a 0.0-1.0 range
-fn normalize_waveform(waveform: &mut [f32]) {
-    if waveform.is_empty() {
-        return;
-    }
-
-    let min = *waveform
-        .iter()
-        .min_by(|a, b| a.total_cmp(b))
-        .unwrap_or(&0.0);
-
-    let max = *waveform
-        .iter()
-        .max_by(|a, b| a.total_cmp(b))
-        .unwrap_or(&1.0);
-
-    match (max - min).abs() < f32::EPSILON {
-        true => waveform.iter_mut().for_each(|value| *value = 0.3),
-        false => waveform
-            .iter_mut()
-            .for_each(|value| *value = (*value - min) / (max - min)),
-    }
-}